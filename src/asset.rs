@@ -0,0 +1,315 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{to_json_binary, Addr, Coin, CosmosMsg, Uint128, WasmMsg};
+use derive_deref::{Deref, DerefMut};
+use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+  send_coins, CoinError, CoinSet, CoinShortfall, ContainerError, MathError, MathResult,
+  TryMinusMut, TryPlusMut, XcosmError, XcosmResult,
+};
+
+/// Asset kind, either a native bank denom or a cw20 token contract.
+#[cw_serde]
+#[derive(Eq, PartialOrd, Ord, Hash)]
+pub enum AssetInfo {
+  Native(String),
+  Cw20(Addr),
+}
+
+impl std::fmt::Display for AssetInfo {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      AssetInfo::Native(denom) => write!(f, "{}", denom),
+      AssetInfo::Cw20(addr) => write!(f, "cw20:{}", addr),
+    }
+  }
+}
+
+/// Amount of a native coin or cw20 token.
+#[cw_serde]
+pub struct Asset {
+  pub info: AssetInfo,
+  pub amount: Uint128,
+}
+
+impl Asset {
+  pub fn native(denom: impl Into<String>, amount: impl Into<Uint128>) -> Self {
+    Self {
+      info: AssetInfo::Native(denom.into()),
+      amount: amount.into(),
+    }
+  }
+
+  pub fn cw20(addr: Addr, amount: impl Into<Uint128>) -> Self {
+    Self {
+      info: AssetInfo::Cw20(addr),
+      amount: amount.into(),
+    }
+  }
+
+  /// Create a message sending the asset, as a bank send for native coins or a cw20
+  /// transfer for tokens.
+  pub fn send(&self, to: &Addr) -> XcosmResult<CosmosMsg> {
+    match &self.info {
+      AssetInfo::Native(denom) => Ok(send_coins(vec![Coin::new(self.amount, denom)], to)),
+      AssetInfo::Cw20(addr) => send_cw20(addr, self.amount, to),
+    }
+  }
+}
+
+impl From<Coin> for Asset {
+  fn from(coin: Coin) -> Self {
+    Self::native(coin.denom, coin.amount)
+  }
+}
+
+impl std::fmt::Display for Asset {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}{}", self.amount, self.info)
+  }
+}
+
+/// Subset of the cw20 execute message used for transfers. See [cw20 spec](https://github.com/CosmWasm/cw-plus/blob/main/packages/cw20/README.md).
+#[cw_serde]
+pub enum Cw20ExecuteMsg {
+  Transfer { recipient: String, amount: Uint128 },
+  Burn { amount: Uint128 },
+}
+
+/// Create cw20 transfer message for a token amount.
+pub fn send_cw20(token: &Addr, amount: Uint128, to: &Addr) -> XcosmResult<CosmosMsg> {
+  Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+    contract_addr: token.to_string(),
+    msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+      recipient: to.to_string(),
+      amount,
+    })?,
+    funds: vec![],
+  }))
+}
+
+/// Create cw20 burn message for a token amount.
+pub fn burn_cw20(token: &Addr, amount: Uint128) -> XcosmResult<CosmosMsg> {
+  Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+    contract_addr: token.to_string(),
+    msg: to_json_binary(&Cw20ExecuteMsg::Burn { amount })?,
+    funds: vec![],
+  }))
+}
+
+/// Sorted and dupe-checked map of native and cw20 assets that serializes as a list.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deref, DerefMut)]
+pub struct AssetSet(BTreeMap<AssetInfo, Uint128>);
+
+impl AssetSet {
+  /// Create a new [`AssetSet`] from a map.
+  pub fn new(assets: BTreeMap<AssetInfo, Uint128>) -> Self {
+    AssetSet(assets)
+  }
+
+  /// Insert the amount into the set.
+  ///
+  /// Requires the asset to not already be present.
+  pub fn try_insert(&mut self, info: AssetInfo, amount: Uint128) -> XcosmResult<&mut Uint128> {
+    match self.entry(info) {
+      Entry::Occupied(entry) => Err(
+        CoinError::DuplicateDenom {
+          denom: entry.key().to_string(),
+        }
+        .into(),
+      ),
+      Entry::Vacant(entry) => Ok(entry.insert(amount)),
+    }
+  }
+
+  /// Get the native coins in the set.
+  pub fn native(&self) -> CoinSet {
+    CoinSet::new(
+      self
+        .iter()
+        .filter_map(|(info, amount)| match info {
+          AssetInfo::Native(denom) => Some((denom.clone(), *amount)),
+          AssetInfo::Cw20(_) => None,
+        })
+        .collect(),
+    )
+  }
+
+  /// Get the cw20 tokens in the set.
+  pub fn cw20(&self) -> impl Iterator<Item=(&Addr, &Uint128)> {
+    self.iter().filter_map(|(info, amount)| match info {
+      AssetInfo::Cw20(addr) => Some((addr, amount)),
+      AssetInfo::Native(_) => None,
+    })
+  }
+
+  /// Require assets to contain the expected asset in at least the expected amount.
+  pub fn expect_asset(&self, expected: &Asset) -> XcosmResult<&Uint128> {
+    self
+      .get(&expected.info)
+      .filter(|&amount| *amount >= expected.amount)
+      .ok_or_else(|| {
+        CoinError::Insufficient {
+          expected: expected.to_string(),
+          // cw20 tokens are keyed by their `cw20:` display form
+          shortfall: CoinShortfall::of_coin(
+            self.get(&expected.info).copied(),
+            &Coin::new(expected.amount, expected.info.to_string()),
+          ),
+        }
+        .into()
+      })
+  }
+
+  /// Require assets to contain all the expected assets in at least the expected amounts.
+  pub fn expect_assets(&self, expected: impl IntoIterator<Item=Asset>) -> XcosmResult {
+    expected
+      .into_iter()
+      .map(|a| self.expect_asset(&a))
+      .collect::<Result<Vec<_>, _>>()?;
+    Ok(())
+  }
+
+  /// Require assets to be empty.
+  pub fn expect_none(&self) -> XcosmResult {
+    if !self.is_empty() {
+      return Err(CoinError::NotEmpty {}.into());
+    }
+    Ok(())
+  }
+
+  /// Require assets to not be empty.
+  pub fn expect_some(&self) -> XcosmResult<&Self> {
+    if self.is_empty() {
+      return Err(CoinError::Empty {}.into());
+    }
+    Ok(self)
+  }
+
+  /// Create messages sending the assets: one bank send for all native coins and one cw20
+  /// transfer per token.
+  ///
+  /// Requires assets to not be empty.
+  pub fn send(&self, to: &Addr) -> XcosmResult<Vec<CosmosMsg>> {
+    self.expect_some()?;
+    let mut msgs = vec![];
+    let native = self.native();
+    if !native.is_empty() {
+      msgs.push(send_coins(native, to));
+    }
+    for (token, amount) in self.cw20() {
+      msgs.push(send_cw20(token, *amount, to)?);
+    }
+    Ok(msgs)
+  }
+}
+
+impl From<CoinSet> for AssetSet {
+  fn from(coins: CoinSet) -> Self {
+    AssetSet(
+      coins
+        .iter()
+        .map(|(denom, amount)| (AssetInfo::Native(denom.clone()), *amount))
+        .collect(),
+    )
+  }
+}
+
+impl TryFrom<Vec<Asset>> for AssetSet {
+  type Error = XcosmError;
+
+  /// Create [`AssetSet`] from an unsorted `Vec<Asset>`.
+  ///
+  /// Requires the provided list to contain no duplicates.
+  fn try_from(raw: Vec<Asset>) -> XcosmResult<Self> {
+    let mut assets = AssetSet::default();
+    for asset in raw {
+      assets.try_insert(asset.info, asset.amount)?;
+    }
+    Ok(assets)
+  }
+}
+
+impl Serialize for AssetSet {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(self.len()))?;
+    for (info, amount) in self.iter() {
+      seq.serialize_element(&Asset {
+        info: info.clone(),
+        amount: *amount,
+      })?;
+    }
+    seq.end()
+  }
+}
+
+impl<'de> Deserialize<'de> for AssetSet {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let raw: Vec<Asset> = Deserialize::deserialize(deserializer)?;
+    AssetSet::try_from(raw).map_err(serde::de::Error::custom)
+  }
+}
+
+impl schemars::JsonSchema for AssetSet {
+  fn schema_name() -> String {
+    Vec::<Asset>::schema_name()
+  }
+
+  fn is_referenceable() -> bool {
+    Vec::<Asset>::is_referenceable()
+  }
+
+  /// Use the `Vec<Asset>` schema to match the serialized list form.
+  fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    Vec::<Asset>::json_schema(gen)
+  }
+}
+
+impl TryPlusMut<&Asset> for AssetSet {
+  type Error = MathError;
+
+  fn try_plus_mut(&mut self, other: &Asset) -> MathResult {
+    let amount = self.entry(other.info.clone()).or_default();
+    *amount = amount.checked_add(other.amount)?;
+    Ok(())
+  }
+}
+
+impl TryMinusMut<&Asset> for AssetSet {
+  type Error = MathError;
+
+  fn try_minus_mut(&mut self, other: &Asset) -> MathResult {
+    if let Some(amount) = self.get_mut(&other.info) {
+      *amount = amount
+        .checked_sub(other.amount)
+        .map_err(|_| ContainerError::Underflow {})?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::XcosmError;
+
+  #[test]
+  fn cw20_shortfall_names_token() {
+    let token = AssetInfo::Cw20(Addr::unchecked("token"));
+    let assets = AssetSet::new(BTreeMap::from([(token.clone(), Uint128::new(40))]));
+    let expected = Asset {
+      info: token,
+      amount: Uint128::new(100),
+    };
+    match assets.expect_asset(&expected) {
+      Err(XcosmError::Coin(CoinError::Insufficient { shortfall, .. })) => {
+        assert_eq!(shortfall.short.get("cw20:token"), Some(&Uint128::new(60)));
+        assert!(shortfall.missing.is_empty());
+      }
+      res => panic!("unexpected result: {res:?}"),
+    }
+  }
+}
@@ -1,25 +1,118 @@
+use std::collections::BTreeMap;
+
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, BlockInfo, Decimal, Event, Timestamp, Uint128};
 
-use crate::XcosmResult;
+use crate::{MathError, XcosmResult};
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
 pub enum AuthError {
   #[error("Requestor is not authorized")]
   Unauthorized {},
+
+  #[error("Unknown role: {role:?}")]
+  UnknownRole { role: String },
+
+  #[error("Requestors have weight {weight}, below the threshold of {threshold}")]
+  InsufficientWeight { weight: u64, threshold: u64 },
+
+  #[error("Weight threshold must be greater than zero")]
+  ZeroThreshold {},
+
+  #[error("Group has no weighted members")]
+  EmptyGroup {},
+
+  #[error("Quorum {quorum} must be greater than zero and at most one")]
+  InvalidQuorum { quorum: Decimal },
+
+  #[error("Requestor is not an admin")]
+  NotAdmin {},
+
+  #[error("Requestor is not an operator")]
+  NotOperator {},
+
+  #[error("{member} is not a member")]
+  NotMember { member: String },
+
+  #[error("Ownership has been renounced")]
+  NoOwner {},
+
+  #[error("No ownership transfer is pending")]
+  NoPendingTransfer {},
+
+  #[error("Ownership transfer has expired, {expiration} has passed")]
+  TransferExpired { expiration: Expiration },
+}
+
+/// Point at which something expires, by block height or time.
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum Expiration {
+  /// Expires once the block height reaches the value.
+  AtHeight(u64),
+  /// Expires once the block time reaches the value.
+  AtTime(Timestamp),
+  /// Never expires.
+  Never {},
 }
 
+impl Default for Expiration {
+  fn default() -> Self {
+    Expiration::Never {}
+  }
+}
+
+impl Expiration {
+  /// Check if expired as of `block`.
+  pub fn is_expired(&self, block: &BlockInfo) -> bool {
+    match self {
+      Expiration::AtHeight(height) => block.height >= *height,
+      Expiration::AtTime(time) => block.time >= *time,
+      Expiration::Never {} => false,
+    }
+  }
+}
+
+impl std::fmt::Display for Expiration {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Expiration::AtHeight(height) => write!(f, "expiration height: {}", height),
+      Expiration::AtTime(time) => write!(f, "expiration time: {}", time),
+      Expiration::Never {} => write!(f, "expiration: never"),
+    }
+  }
+}
+
+/// Type of the events describing changes to an [`Authorized`] group.
+pub const AUTHORIZED_EVENT_TYPE: &str = "authorized";
+
 /// Auth handler.
 #[cw_serde]
+#[derive(Default)]
 pub enum Authorized<T: Eq+ToString=Addr> {
   /// Single authorized address.
   One(T),
   /// Multiple authorized addresses.
   Many(Vec<T>),
   /// No authorized addresses.
+  #[default]
   None,
   /// All addresses authorized.
   Any,
+  /// Authorized addresses with voting weights.
+  Weighted(Vec<(T, u32)>),
+  /// Authorized addresses that lapse once expired. Only checked against a block by
+  /// [`Authorized::authorize_at`] and [`Authorized::active_at`]; elsewhere only members that
+  /// never expire are authorized.
+  Expiring(Vec<(T, Expiration)>),
+}
+
+/// Members of an [`Authorized::Expiring`] group that never expire.
+fn permanent<T>(members: &[(T, Expiration)]) -> impl Iterator<Item=&T> {
+  members
+    .iter()
+    .filter(|(_, expiration)| *expiration == Expiration::Never {})
+    .map(|(member, _)| member)
 }
 
 impl<T: Eq+ToString> Authorized<T> {
@@ -51,44 +144,85 @@ impl<T: Eq+ToString> Authorized<T> {
       }
       Authorized::None => return Err(AuthError::Unauthorized {}.into()),
       Authorized::Any => return Ok(()),
+      Authorized::Weighted(_) | Authorized::Expiring(_) => {
+        if self.weight_of(requestor) == 0 {
+          return Err(AuthError::Unauthorized {}.into());
+        }
+      }
     };
     Ok(())
   }
 
-  /// Authorize any of the requestors.
+  /// Authorize a single requestor as of `block`, skipping expired members.
+  ///
+  /// Requires requestor to match authorized.
+  pub fn authorize_at(&self, requestor: &T, block: &BlockInfo) -> XcosmResult
+  where T: Clone {
+    self.active_at(block).authorize(requestor)
+  }
+
+  /// Get the group as of `block`, removing expired members of an [`Authorized::Expiring`]
+  /// group and treating the rest as never expiring.
+  pub fn active_at(&self, block: &BlockInfo) -> Self
+  where T: Clone {
+    match self {
+      Authorized::Expiring(members) => Authorized::Expiring(
+        members
+          .iter()
+          .filter(|(_, expiration)| !expiration.is_expired(block))
+          .map(|(member, _)| (member.clone(), Expiration::Never {}))
+          .collect(),
+      ),
+      authorized => authorized.clone(),
+    }
+  }
+
+  /// Authorize any of the requestors, returning the distinct requestors that matched.
   ///
   /// Requires at least one of `requestors` to match authorized.
-  pub fn authorize_any(&self, requestors: &Vec<T>) -> XcosmResult {
+  pub fn authorize_any<'a>(&self, requestors: &'a [T]) -> XcosmResult<Vec<&'a T>> {
     match match self {
       Authorized::One(authorized) => requestors.contains(authorized),
       Authorized::Many(authorized) => requestors.iter().any(|r| authorized.contains(r)),
       Authorized::None => false,
       Authorized::Any => true,
+      Authorized::Weighted(_) | Authorized::Expiring(_) => {
+        requestors.iter().any(|r| self.weight_of(r) > 0)
+      }
     } {
-      true => Ok(()),
+      true => Ok(self.matching(requestors)),
       false => Err(AuthError::Unauthorized {}.into()),
     }
   }
 
   /// Authorize all of the requestors.
   ///
-  /// Requires all of `requestors` to match authorized.
-  pub fn authorize_all(&self, requestors: &Vec<T>) -> XcosmResult {
+  /// Requires all of `requestors` to match authorized. For [`Authorized::Weighted`], requires
+  /// every positive-weight member to be among `requestors`.
+  pub fn authorize_all(&self, requestors: &[T]) -> XcosmResult {
     match match self {
       Authorized::One(authorized) => requestors.contains(authorized),
       Authorized::Many(authorized) => requestors.iter().all(|r| authorized.contains(r)),
       Authorized::None => false,
       Authorized::Any => true,
+      Authorized::Weighted(authorized) => authorized
+        .iter()
+        .filter(|(_, weight)| *weight > 0)
+        .all(|(member, _)| requestors.contains(member)),
+      Authorized::Expiring(authorized) => requestors
+        .iter()
+        .all(|r| permanent(authorized).any(|a| a == r)),
     } {
       true => Ok(()),
       false => Err(AuthError::Unauthorized {}.into()),
     }
   }
 
-  /// Authorize at least `min` of the requestors.
+  /// Authorize at least `min` of the requestors, returning the distinct requestors that
+  /// matched.
   ///
   /// Requires at least `min` of `requestors` to match authorized.
-  pub fn authorize_at_least(&self, requestors: &Vec<T>, min: u32) -> XcosmResult {
+  pub fn authorize_at_least<'a>(&self, requestors: &'a [T], min: u32) -> XcosmResult<Vec<&'a T>> {
     match match self {
       Authorized::One(authorized) => requestors.contains(authorized),
       Authorized::Many(authorized) => {
@@ -96,26 +230,615 @@ impl<T: Eq+ToString> Authorized<T> {
       }
       Authorized::None => false,
       Authorized::Any => true,
+      Authorized::Weighted(authorized) => {
+        authorized
+          .iter()
+          .filter(|(member, weight)| *weight > 0 && requestors.contains(member))
+          .count() as u32
+          >= min
+      }
+      Authorized::Expiring(authorized) => {
+        permanent(authorized)
+          .filter(|a| requestors.contains(a))
+          .count() as u32
+          >= min
+      }
     } {
-      true => Ok(()),
+      true => Ok(self.matching(requestors)),
       false => Err(AuthError::Unauthorized {}.into()),
     }
   }
-}
 
-impl<T: Eq+ToString> Default for Authorized<T> {
-  fn default() -> Self {
-    Authorized::None
+  /// Get the distinct requestors with a positive weight, in order. See
+  /// [`Authorized::weight_of`].
+  pub fn matching<'a>(&self, requestors: &'a [T]) -> Vec<&'a T> {
+    requestors
+      .iter()
+      .enumerate()
+      .filter(|(i, r)| self.weight_of(r) > 0 && !requestors[..*i].contains(r))
+      .map(|(_, r)| r)
+      .collect()
+  }
+
+  /// Authorize the requestors by combined weight.
+  ///
+  /// Requires the summed weight of matching `requestors` to be at least `min_weight`. Members
+  /// of unweighted groups count as weight 1. Zero thresholds and groups without members are
+  /// rejected rather than authorizing anyone.
+  pub fn authorize_weighted(&self, requestors: &[T], min_weight: u32) -> XcosmResult {
+    if min_weight == 0 {
+      return Err(AuthError::ZeroThreshold {}.into());
+    }
+    let weight = match self {
+      Authorized::Any => return Ok(()),
+      _ if self.total_weight() == 0 => return Err(AuthError::EmptyGroup {}.into()),
+      Authorized::One(authorized) => requestors.contains(authorized) as u32,
+      Authorized::Many(authorized) => {
+        authorized.iter().filter(|a| requestors.contains(a)).count() as u32
+      }
+      Authorized::None => 0,
+      Authorized::Weighted(authorized) => authorized
+        .iter()
+        .filter(|(member, _)| requestors.contains(member))
+        .fold(0u32, |total, (_, weight)| total.saturating_add(*weight)),
+      Authorized::Expiring(authorized) => permanent(authorized)
+        .filter(|a| requestors.contains(a))
+        .count() as u32,
+    };
+    match weight >= min_weight {
+      true => Ok(()),
+      false => Err(
+        AuthError::InsufficientWeight {
+          weight: weight.into(),
+          threshold: min_weight.into(),
+        }
+        .into(),
+      ),
+    }
+  }
+
+  /// Authorize the requestors by their fraction of the total weight.
+  ///
+  /// Requires the combined weight of matching `requestors` to be at least `quorum` of
+  /// [`Authorized::total_weight`], rounded up. `quorum` must be in `(0, 1]`.
+  pub fn authorize_quorum(&self, requestors: &[T], quorum: Decimal) -> XcosmResult {
+    if quorum.is_zero() || quorum > Decimal::one() {
+      return Err(AuthError::InvalidQuorum { quorum }.into());
+    }
+    if matches!(self, Authorized::Any) {
+      return Ok(());
+    }
+    let threshold = Uint128::from(self.total_weight())
+      .checked_mul_ceil(quorum)
+      .map_err(MathError::from)?;
+    self.authorize_weighted(
+      requestors,
+      u32::try_from(threshold.u128()).unwrap_or(u32::MAX),
+    )
+  }
+
+  /// Get the combined weight of all members.
+  ///
+  /// Members of unweighted groups have weight 1. [`Authorized::Any`] has no fixed members and
+  /// reports zero, as do expiring members with a finite expiration.
+  pub fn total_weight(&self) -> u32 {
+    match self {
+      Authorized::One(_) => 1,
+      Authorized::Many(authorized) => authorized.len() as u32,
+      Authorized::None | Authorized::Any => 0,
+      Authorized::Weighted(authorized) => authorized
+        .iter()
+        .fold(0u32, |total, (_, weight)| total.saturating_add(*weight)),
+      Authorized::Expiring(authorized) => permanent(authorized).count() as u32,
+    }
+  }
+
+  /// Get the weight of a member, or zero if not a member.
+  ///
+  /// Members of unweighted groups have weight 1, and every requestor has weight 1 under
+  /// [`Authorized::Any`].
+  pub fn weight_of(&self, requestor: &T) -> u32 {
+    match self {
+      Authorized::One(authorized) => (authorized == requestor) as u32,
+      Authorized::Many(authorized) => authorized.contains(requestor) as u32,
+      Authorized::None => 0,
+      Authorized::Any => 1,
+      Authorized::Weighted(authorized) => authorized
+        .iter()
+        .find(|(member, _)| member == requestor)
+        .map_or(0, |(_, weight)| *weight),
+      Authorized::Expiring(authorized) => permanent(authorized).any(|a| a == requestor) as u32,
+    }
   }
 }
 
-impl<T: Eq+ToString, U: From<T>> Into<Vec<U>> for Authorized<T> {
-  fn into(self) -> Vec<U> {
+impl<T: Eq+ToString+Clone> Authorized<T> {
+  /// Check if `member` is listed in the group. Always false under [`Authorized::Any`].
+  pub fn is_member(&self, member: &T) -> bool {
     match self {
+      Authorized::One(authorized) => authorized == member,
+      Authorized::Many(authorized) => authorized.contains(member),
+      Authorized::None | Authorized::Any => false,
+      Authorized::Weighted(authorized) => authorized.iter().any(|(a, _)| a == member),
+      Authorized::Expiring(authorized) => authorized.iter().any(|(a, _)| a == member),
+    }
+  }
+
+  /// Add a member, returning an event describing the change or `None` if already a member.
+  ///
+  /// New members of a weighted group are given weight 1, and new members of an expiring group
+  /// never expire. Groups open to [`Authorized::Any`] are left unchanged.
+  pub fn add_member(&mut self, member: T) -> Option<Event> {
+    if self.is_member(&member) {
+      return None;
+    }
+    let event = Event::new(AUTHORIZED_EVENT_TYPE)
+      .add_attribute("action", "add_member")
+      .add_attribute("member", member.to_string());
+    match self {
+      Authorized::One(authorized) => {
+        *self = Authorized::Many(vec![authorized.clone(), member]);
+      }
+      Authorized::Many(authorized) => authorized.push(member),
+      Authorized::None => *self = Authorized::One(member),
+      Authorized::Any => return None,
+      Authorized::Weighted(authorized) => authorized.push((member, 1)),
+      Authorized::Expiring(authorized) => authorized.push((member, Expiration::Never {})),
+    }
+    Some(event)
+  }
+
+  /// Remove a member, returning an event describing the change or `None` if not a member.
+  ///
+  /// Groups left with one member become [`Authorized::One`] and empty groups become
+  /// [`Authorized::None`], except that a weighted or expiring group keeps its variant until
+  /// empty.
+  pub fn remove_member(&mut self, member: &T) -> Option<Event> {
+    if !self.is_member(member) {
+      return None;
+    }
+    *self = match std::mem::replace(self, Authorized::None) {
+      Authorized::Many(mut authorized) => {
+        authorized.retain(|a| a != member);
+        Authorized::new(authorized.as_slice())
+      }
+      Authorized::Weighted(mut authorized) => {
+        authorized.retain(|(a, _)| a != member);
+        match authorized.is_empty() {
+          true => Authorized::None,
+          false => Authorized::Weighted(authorized),
+        }
+      }
+      Authorized::Expiring(mut authorized) => {
+        authorized.retain(|(a, _)| a != member);
+        match authorized.is_empty() {
+          true => Authorized::None,
+          false => Authorized::Expiring(authorized),
+        }
+      }
+      _ => Authorized::None,
+    };
+    Some(
+      Event::new(AUTHORIZED_EVENT_TYPE)
+        .add_attribute("action", "remove_member")
+        .add_attribute("member", member.to_string()),
+    )
+  }
+
+  /// Replace the member `old` with `new`, keeping its weight or expiration, and return an
+  /// event describing the change. If `new` is already a member, `old` is just removed.
+  ///
+  /// Requires `old` to be a member.
+  pub fn replace(&mut self, old: &T, new: T) -> XcosmResult<Event> {
+    if !self.is_member(old) {
+      return Err(
+        AuthError::NotMember {
+          member: old.to_string(),
+        }
+        .into(),
+      );
+    }
+    let event = Event::new(AUTHORIZED_EVENT_TYPE)
+      .add_attribute("action", "replace")
+      .add_attribute("old", old.to_string())
+      .add_attribute("new", new.to_string());
+    if self.is_member(&new) {
+      self.remove_member(old);
+      return Ok(event);
+    }
+    match self {
+      Authorized::One(authorized) => *authorized = new,
+      Authorized::Many(authorized) => authorized
+        .iter_mut()
+        .filter(|a| *a == old)
+        .for_each(|a| *a = new.clone()),
+      Authorized::None | Authorized::Any => {}
+      Authorized::Weighted(authorized) => authorized
+        .iter_mut()
+        .filter(|(a, _)| a == old)
+        .for_each(|(a, _)| *a = new.clone()),
+      Authorized::Expiring(authorized) => authorized
+        .iter_mut()
+        .filter(|(a, _)| a == old)
+        .for_each(|(a, _)| *a = new.clone()),
+    }
+    Ok(event)
+  }
+}
+
+impl<T: Eq+ToString, U: From<T>> From<Authorized<T>> for Vec<U> {
+  fn from(authorized: Authorized<T>) -> Self {
+    match authorized {
       Authorized::One(authorized) => vec![authorized.into()],
       Authorized::Many(authorized) => authorized.into_iter().map(Into::into).collect(),
       Authorized::None => vec![],
       Authorized::Any => vec![],
+      Authorized::Weighted(authorized) => authorized
+        .into_iter()
+        .map(|(member, _)| member.into())
+        .collect(),
+      Authorized::Expiring(authorized) => authorized
+        .into_iter()
+        .map(|(member, _)| member.into())
+        .collect(),
+    }
+  }
+}
+
+/// Contract owner with two-step transfers, where the new owner must accept before the
+/// transfer takes effect.
+#[cw_serde]
+#[derive(Default)]
+pub struct Ownership {
+  /// Current owner, or `None` if renounced.
+  pub owner: Option<Addr>,
+  /// Proposed new owner, if a transfer is pending.
+  pub pending_owner: Option<Addr>,
+  /// Point after which the pending transfer can no longer be accepted.
+  #[serde(default)]
+  pub pending_expiry: Expiration,
+}
+
+impl Ownership {
+  pub fn new(owner: Addr) -> Self {
+    Self {
+      owner: Some(owner),
+      pending_owner: None,
+      pending_expiry: Expiration::default(),
+    }
+  }
+
+  /// Get the owner as an authorized group, empty if renounced.
+  pub fn authorized(&self) -> Authorized {
+    match &self.owner {
+      Some(owner) => Authorized::One(owner.clone()),
+      None => Authorized::None,
+    }
+  }
+
+  /// Require `sender` to be the owner.
+  pub fn assert_owner(&self, sender: &Addr) -> XcosmResult {
+    match &self.owner {
+      Some(_) => self.authorized().authorize(sender),
+      None => Err(AuthError::NoOwner {}.into()),
+    }
+  }
+
+  /// Propose transferring ownership to `new_owner`, replacing any pending transfer. The new
+  /// owner must accept before `expiry`.
+  ///
+  /// Requires `sender` to be the owner and `expiry` to not have passed as of `block`.
+  pub fn propose_transfer(
+    &mut self,
+    sender: &Addr,
+    new_owner: Addr,
+    expiry: Expiration,
+    block: &BlockInfo,
+  ) -> XcosmResult {
+    self.assert_owner(sender)?;
+    if expiry.is_expired(block) {
+      return Err(AuthError::TransferExpired { expiration: expiry }.into());
+    }
+    self.pending_owner = Some(new_owner);
+    self.pending_expiry = expiry;
+    Ok(())
+  }
+
+  /// Cancel the pending transfer.
+  ///
+  /// Requires `sender` to be the owner and a transfer to be pending.
+  pub fn cancel_transfer(&mut self, sender: &Addr) -> XcosmResult {
+    self.assert_owner(sender)?;
+    if self.pending_owner.take().is_none() {
+      return Err(AuthError::NoPendingTransfer {}.into());
     }
+    self.pending_expiry = Expiration::default();
+    Ok(())
+  }
+
+  /// Accept the pending transfer, making `sender` the owner.
+  ///
+  /// Requires `sender` to be the pending owner and the transfer to not have expired as of
+  /// `block`.
+  pub fn accept(&mut self, sender: &Addr, block: &BlockInfo) -> XcosmResult {
+    let pending_owner = self
+      .pending_owner
+      .as_ref()
+      .ok_or(AuthError::NoPendingTransfer {})?;
+    Authorized::One(pending_owner.clone()).authorize(sender)?;
+    if self.pending_expiry.is_expired(block) {
+      return Err(
+        AuthError::TransferExpired {
+          expiration: self.pending_expiry,
+        }
+        .into(),
+      );
+    }
+    self.owner = self.pending_owner.take();
+    self.pending_expiry = Expiration::default();
+    Ok(())
+  }
+
+  /// Give up ownership permanently, also cancelling any pending transfer.
+  ///
+  /// Requires `sender` to be the owner.
+  pub fn renounce(&mut self, sender: &Addr) -> XcosmResult {
+    self.assert_owner(sender)?;
+    *self = Self::default();
+    Ok(())
+  }
+}
+
+/// Two-tier authorization where admins manage the operators and pass operator checks.
+#[cw_serde]
+#[derive(Default)]
+pub struct AdminOperators {
+  pub admins: Authorized,
+  pub operators: Authorized,
+}
+
+impl AdminOperators {
+  pub fn new(admins: Authorized, operators: Authorized) -> Self {
+    Self { admins, operators }
+  }
+
+  /// Require `sender` to be an admin.
+  pub fn assert_admin(&self, sender: &Addr) -> XcosmResult {
+    self
+      .admins
+      .authorize(sender)
+      .map_err(|_| AuthError::NotAdmin {}.into())
+  }
+
+  /// Require `sender` to be an operator or an admin.
+  pub fn assert_operator(&self, sender: &Addr) -> XcosmResult {
+    self
+      .admins
+      .authorize(sender)
+      .or_else(|_| self.operators.authorize(sender))
+      .map_err(|_| AuthError::NotOperator {}.into())
+  }
+
+  /// Add an operator, returning an event describing the change or `None` if already an
+  /// operator.
+  ///
+  /// Requires `sender` to be an admin.
+  pub fn add_operator(&mut self, sender: &Addr, operator: Addr) -> XcosmResult<Option<Event>> {
+    self.assert_admin(sender)?;
+    Ok(self.operators.add_member(operator))
+  }
+
+  /// Remove an operator, returning an event describing the change or `None` if not an
+  /// operator.
+  ///
+  /// Requires `sender` to be an admin.
+  pub fn remove_operator(&mut self, sender: &Addr, operator: &Addr) -> XcosmResult<Option<Event>> {
+    self.assert_admin(sender)?;
+    Ok(self.operators.remove_member(operator))
+  }
+}
+
+/// Named roles, each with its own authorized group.
+#[cw_serde]
+#[derive(Default)]
+pub struct RoleRegistry {
+  pub roles: BTreeMap<String, Authorized<Addr>>,
+}
+
+impl RoleRegistry {
+  /// Authorize a requestor for a role.
+  ///
+  /// Requires the role to exist and the requestor to be authorized for it.
+  pub fn authorize_role(&self, role: &str, requestor: &Addr) -> XcosmResult {
+    self
+      .roles
+      .get(role)
+      .ok_or_else(|| AuthError::UnknownRole {
+        role: role.to_string(),
+      })?
+      .authorize(requestor)
+  }
+
+  /// Grant a role to an address, creating the role if needed.
+  ///
+  /// New members of a weighted role are given weight 1, and new members of an expiring role
+  /// never expire.
+  pub fn grant(&mut self, role: &str, addr: Addr) {
+    self
+      .roles
+      .entry(role.to_string())
+      .or_default()
+      .add_member(addr);
+  }
+
+  /// Grant a role to an address until `expiration`, creating the role if needed and
+  /// converting it to an [`Authorized::Expiring`] group. Existing members never expire, and
+  /// the weights of a weighted role are dropped.
+  ///
+  /// Roles open to [`Authorized::Any`] are left unchanged.
+  pub fn grant_until(&mut self, role: &str, addr: Addr, expiration: Expiration) {
+    let authorized = self.roles.entry(role.to_string()).or_default();
+    let mut members = match std::mem::take(authorized) {
+      Authorized::Any => {
+        *authorized = Authorized::Any;
+        return;
+      }
+      Authorized::Expiring(members) => members,
+      group => Vec::<Addr>::from(group)
+        .into_iter()
+        .map(|member| (member, Expiration::Never {}))
+        .collect(),
+    };
+    members.retain(|(member, _)| *member != addr);
+    members.push((addr, expiration));
+    *authorized = Authorized::Expiring(members);
+  }
+
+  /// Authorize a requestor for a role as of `block`, skipping expired members.
+  ///
+  /// Requires the role to exist and the requestor to be authorized for it.
+  pub fn authorize_role_at(&self, role: &str, requestor: &Addr, block: &BlockInfo) -> XcosmResult {
+    self
+      .roles
+      .get(role)
+      .ok_or_else(|| AuthError::UnknownRole {
+        role: role.to_string(),
+      })?
+      .authorize_at(requestor, block)
+  }
+
+  /// Revoke a role from an address.
+  ///
+  /// Requires the role to exist. Roles open to [`Authorized::Any`] are left unchanged.
+  pub fn revoke(&mut self, role: &str, addr: &Addr) -> XcosmResult {
+    self
+      .roles
+      .get_mut(role)
+      .ok_or_else(|| AuthError::UnknownRole {
+        role: role.to_string(),
+      })?
+      .remove_member(addr);
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::XcosmError;
+
+  fn addr(addr: &str) -> Addr {
+    Addr::unchecked(addr)
+  }
+
+  fn weighted() -> Authorized {
+    Authorized::Weighted(vec![(addr("a"), 3), (addr("b"), 1), (addr("c"), 1)])
+  }
+
+  #[test]
+  fn weighted_rejects_zero_threshold() {
+    assert!(weighted().authorize_weighted(&[addr("z")], 0).is_err());
+    assert!(Authorized::<Addr>::None
+      .authorize_weighted(&[addr("z")], 0)
+      .is_err());
+  }
+
+  #[test]
+  fn weighted_rejects_empty_group() {
+    let empty = Authorized::<Addr>::Weighted(vec![]);
+    assert!(empty.authorize_weighted(&[addr("z")], 1).is_err());
+    assert!(empty
+      .authorize_quorum(&[addr("z")], Decimal::percent(50))
+      .is_err());
+  }
+
+  #[test]
+  fn quorum_rounds_up() {
+    // 60% of 5 is 3
+    let quorum = Decimal::percent(60);
+    assert!(weighted().authorize_quorum(&[addr("a")], quorum).is_ok());
+    assert!(weighted()
+      .authorize_quorum(&[addr("b"), addr("c")], quorum)
+      .is_err());
+    // 61% of 5 rounds up to 4
+    let quorum = Decimal::percent(61);
+    assert!(weighted().authorize_quorum(&[addr("a")], quorum).is_err());
+    assert!(weighted()
+      .authorize_quorum(&[addr("a"), addr("b")], quorum)
+      .is_ok());
+  }
+
+  #[test]
+  fn quorum_out_of_range() {
+    let all = [addr("a"), addr("b"), addr("c")];
+    assert!(weighted().authorize_quorum(&all, Decimal::zero()).is_err());
+    assert!(weighted().authorize_quorum(&all, Decimal::one()).is_ok());
+    assert!(weighted()
+      .authorize_quorum(&all, Decimal::percent(101))
+      .is_err());
+  }
+
+  #[test]
+  fn role_grant_and_authorize() {
+    let mut registry = RoleRegistry::default();
+    registry.grant("minter", addr("a"));
+    registry.grant("minter", addr("b"));
+    assert!(registry.authorize_role("minter", &addr("a")).is_ok());
+    assert!(registry.authorize_role("minter", &addr("b")).is_ok());
+    registry.revoke("minter", &addr("a")).unwrap();
+    assert!(registry.authorize_role("minter", &addr("a")).is_err());
+  }
+
+  #[test]
+  fn role_rejects_non_member() {
+    let mut registry = RoleRegistry::default();
+    registry.grant("minter", addr("a"));
+    assert!(matches!(
+      registry.authorize_role("minter", &addr("z")),
+      Err(XcosmError::Auth(AuthError::Unauthorized {}))
+    ));
+  }
+
+  #[test]
+  fn role_rejects_unknown_role() {
+    let mut registry = RoleRegistry::default();
+    registry.grant("minter", addr("a"));
+    assert!(matches!(
+      registry.authorize_role("burner", &addr("a")),
+      Err(XcosmError::Auth(AuthError::UnknownRole { role })) if role == "burner"
+    ));
+    assert!(registry.revoke("burner", &addr("a")).is_err());
+  }
+
+  #[test]
+  fn weighted_single_heavy_signer() {
+    assert!(weighted().authorize_weighted(&[addr("a")], 3).is_ok());
+    assert!(matches!(
+      weighted().authorize_weighted(&[addr("b")], 3),
+      Err(XcosmError::Auth(AuthError::InsufficientWeight {
+        weight: 1,
+        threshold: 3
+      }))
+    ));
+  }
+
+  #[test]
+  fn weighted_light_signers_together() {
+    let group = Authorized::Weighted(vec![
+      (addr("a"), 5),
+      (addr("b"), 1),
+      (addr("c"), 1),
+      (addr("d"), 1),
+    ]);
+    assert!(group
+      .authorize_weighted(&[addr("b"), addr("c")], 3)
+      .is_err());
+    assert!(group
+      .authorize_weighted(&[addr("b"), addr("c"), addr("d")], 3)
+      .is_ok());
+    // repeated and unknown requestors add nothing
+    assert!(group
+      .authorize_weighted(&[addr("b"), addr("b"), addr("z")], 2)
+      .is_err());
   }
 }
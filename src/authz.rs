@@ -0,0 +1,110 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, AnyMsg, CosmosMsg, Timestamp};
+use prost::Message;
+
+use crate::{proto, CoinSet, XcosmResult};
+
+/// Permission granted to a grantee with `x/authz`.
+#[cw_serde]
+pub enum Authorization {
+  /// Execute any message of the type.
+  Generic { msg_type_url: String },
+  /// Send up to `spend_limit` on the granter's behalf, only to `allow_list` if not empty.
+  Send {
+    spend_limit: CoinSet,
+    allow_list: Vec<Addr>,
+  },
+}
+
+impl Authorization {
+  /// Encode as a protobuf `Any` of the matching `cosmos.authz` or `cosmos.bank` type.
+  fn encode_any(&self) -> XcosmResult<proto::Any> {
+    Ok(match self {
+      Authorization::Generic { msg_type_url } => proto::Any {
+        type_url: "/cosmos.authz.v1beta1.GenericAuthorization".to_string(),
+        value: proto::GenericAuthorization {
+          msg: msg_type_url.clone(),
+        }
+        .encode_to_vec(),
+      },
+      Authorization::Send {
+        spend_limit,
+        allow_list,
+      } => proto::Any {
+        type_url: "/cosmos.bank.v1beta1.SendAuthorization".to_string(),
+        value: proto::SendAuthorization {
+          spend_limit: spend_limit
+            .without_zeros()
+            .expect_some()?
+            .iter()
+            .map(|(denom, amount)| proto::Coin {
+              denom: denom.clone(),
+              amount: amount.to_string(),
+            })
+            .collect(),
+          allow_list: allow_list.iter().map(Addr::to_string).collect(),
+        }
+        .encode_to_vec(),
+      },
+    })
+  }
+}
+
+/// Create an `x/authz` message granting `authorization` from `granter` to `grantee`, expiring
+/// at `expiration` if given.
+///
+/// Requires a send authorization to have a non-zero spend limit.
+pub fn authz_grant(
+  granter: &Addr,
+  grantee: &Addr,
+  authorization: &Authorization,
+  expiration: Option<Timestamp>,
+) -> XcosmResult<CosmosMsg> {
+  let msg = proto::MsgGrant {
+    granter: granter.to_string(),
+    grantee: grantee.to_string(),
+    grant: Some(proto::Grant {
+      authorization: Some(authorization.encode_any()?),
+      expiration: expiration.map(|time| proto::Timestamp {
+        seconds: time.seconds() as i64,
+        nanos: time.subsec_nanos() as i32,
+      }),
+    }),
+  };
+  Ok(CosmosMsg::Any(AnyMsg {
+    type_url: "/cosmos.authz.v1beta1.MsgGrant".to_string(),
+    value: msg.encode_to_vec().into(),
+  }))
+}
+
+/// Create an `x/authz` message revoking the grant from `granter` to `grantee` for messages of
+/// `msg_type_url`. Send authorizations are revoked with `/cosmos.bank.v1beta1.MsgSend`.
+pub fn authz_revoke(granter: &Addr, grantee: &Addr, msg_type_url: &str) -> CosmosMsg {
+  let msg = proto::MsgRevoke {
+    granter: granter.to_string(),
+    grantee: grantee.to_string(),
+    msg_type_url: msg_type_url.to_string(),
+  };
+  CosmosMsg::Any(AnyMsg {
+    type_url: "/cosmos.authz.v1beta1.MsgRevoke".to_string(),
+    value: msg.encode_to_vec().into(),
+  })
+}
+
+/// Create an `x/authz` message executing `msgs` by `grantee` under grants given to it.
+pub fn authz_exec(grantee: &Addr, msgs: Vec<AnyMsg>) -> CosmosMsg {
+  let msg = proto::MsgExec {
+    grantee: grantee.to_string(),
+    msgs: msgs
+      .into_iter()
+      .map(|msg| proto::Any {
+        type_url: msg.type_url,
+        value: msg.value.to_vec(),
+      })
+      .collect(),
+  };
+  CosmosMsg::Any(AnyMsg {
+    type_url: "/cosmos.authz.v1beta1.MsgExec".to_string(),
+    value: msg.encode_to_vec().into(),
+  })
+}
@@ -1,21 +1,43 @@
-use std::collections::btree_map::{Entry, Iter as BTreeMapIter};
-use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::collections::btree_map::{Entry, IntoIter as BTreeMapIntoIter, Iter as BTreeMapIter};
+use std::collections::{BTreeMap, BTreeSet};
 use std::iter::Map;
+use std::ops::{Bound, RangeBounds};
 
 use cosmwasm_schema::cw_serde;
+#[cfg(feature = "cosmwasm_2_0")]
+use cosmwasm_std::AnyMsg;
 use cosmwasm_std::{
-  to_json_binary, Addr, AnyMsg, BankMsg, Coin, Coins, CoinsError, CosmosMsg, Uint128,
+  Addr, Api, BankMsg, CanonicalAddr, Coin, Coins, CoinsError, CosmosMsg, Decimal, Decimal256,
+  Fraction, Uint128, Uint256,
 };
+#[cfg(feature = "stargate")]
+use cosmwasm_std::{IbcMsg, IbcTimeout};
 use derive_deref::{Deref, DerefMut};
-use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
+use serde::{
+  de::{self, MapAccess, SeqAccess, Visitor},
+  ser::SerializeSeq,
+  Deserialize, Deserializer, Serialize, Serializer,
+};
+use sha2::{Digest, Sha256};
 
-use crate::{TryMinusMut, XcosmError, XcosmResult};
+use crate::{
+  math::add_amount, validate_denom, ContainerError, MathError, MathResult, Rounding, TryMinus,
+  TryPlusMut, ValidateError, ValueError, XcosmError, XcosmResult,
+};
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
 pub enum CoinError {
   /// Coins do not meet the expected amount.
-  #[error("Insufficient coins provided: expected {expected:?}")]
-  Insufficient { expected: String },
+  #[error("Insufficient coins provided: expected {expected:?}, {shortfall}")]
+  Insufficient {
+    expected: String,
+    shortfall: CoinShortfall,
+  },
+
+  /// Coins exceed the allowed amount.
+  #[error("Excessive coins provided: limit {limit:?}")]
+  Excessive { limit: String },
 
   /// Expected no coins, but received some.
   #[error("Empty coins required")]
@@ -37,6 +59,22 @@ pub enum CoinError {
   #[error("Input coins and output coins must have equal values")]
   IoMismatch {},
 
+  /// Coins contain a denom that is not allowed.
+  #[error("Unexpected denom in coins: {denom:?}")]
+  UnexpectedDenom { denom: String },
+
+  /// Coins contain more denoms than allowed.
+  #[error("Too many denoms in coins: {count} exceeds maximum of {max}")]
+  TooManyDenoms { max: usize, count: usize },
+
+  /// Coins contain a zero amount where none are allowed.
+  #[error("Zero amount in coins: {denom:?}")]
+  ZeroAmount { denom: String },
+
+  /// Coin string could not be parsed.
+  #[error("Invalid coin {coin:?}: {reason}")]
+  Invalid { coin: String, reason: String },
+
   /// Coin error which _should_ never occur.
   #[error("Unexpected coin error: {msg:?}")]
   Unexpected { msg: String },
@@ -53,8 +91,440 @@ impl From<CoinsError> for CoinError {
   }
 }
 
+/// Token denom, validated against the Cosmos SDK denom rules.
+///
+/// Construct through [`crate::ApiValidator`]: `let denom: Denom = api.validate(&"uatom")?`
+#[cw_serde]
+#[derive(Eq, PartialOrd, Ord, Hash)]
+pub struct Denom(String);
+
+impl Denom {
+  /// Create a [`Denom`] without validation.
+  pub fn unchecked(denom: impl Into<String>) -> Self {
+    Denom(denom.into())
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+
+  pub fn into_string(self) -> String {
+    self.0
+  }
+}
+
+impl AsRef<str> for Denom {
+  fn as_ref(&self) -> &str {
+    &self.0
+  }
+}
+
+impl std::ops::Deref for Denom {
+  type Target = str;
+
+  fn deref(&self) -> &str {
+    &self.0
+  }
+}
+
+impl std::fmt::Display for Denom {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.0)
+  }
+}
+
+impl From<Denom> for String {
+  fn from(denom: Denom) -> Self {
+    denom.0
+  }
+}
+
+/// IBC denom trace, e.g. `transfer/channel-0/uatom`, from which the `ibc/{hash}` voucher
+/// denom is derived.
+#[cw_serde]
+#[derive(Eq)]
+pub struct DenomTrace {
+  /// Port and channel hops, e.g. `transfer/channel-0`. Empty for native denoms.
+  pub path: String,
+  pub base_denom: String,
+}
+
+impl DenomTrace {
+  /// Parse a full trace like `transfer/channel-0/uatom`.
+  ///
+  /// Leading `{port}/{channel}` pairs form the path and the rest is the base denom, which may
+  /// itself contain slashes. Requires valid port and channel identifiers and a valid base
+  /// denom.
+  pub fn parse(trace: &str) -> XcosmResult<Self> {
+    let parts: Vec<&str> = trace.split('/').collect();
+    let mut hops = 0;
+    while hops * 2 + 2 < parts.len() && is_channel_id(parts[hops * 2 + 1]) {
+      hops += 1;
+    }
+    let (path, base) = parts.split_at(hops * 2);
+    let res = Self {
+      path: path.join("/"),
+      base_denom: base.join("/"),
+    };
+    res.validate()?;
+    Ok(res)
+  }
+
+  /// Require valid port and channel identifiers in the path and a valid base denom.
+  pub fn validate(&self) -> XcosmResult {
+    let not_valid = |reason: &str| ValidateError::NotValid {
+      kind: "denom trace".to_string(),
+      reason: format!("{}: {}", self.full_path(), reason),
+    };
+    if !self.path.is_empty() {
+      let parts: Vec<&str> = self.path.split('/').collect();
+      if parts.len() % 2 != 0 {
+        return Err(not_valid("path must be {port}/{channel} pairs").into());
+      }
+      for hop in parts.chunks(2) {
+        if !is_port_id(hop[0]) {
+          return Err(not_valid(&format!("invalid port {:?}", hop[0])).into());
+        }
+        if !is_channel_id(hop[1]) {
+          return Err(not_valid(&format!("invalid channel {:?}", hop[1])).into());
+        }
+      }
+    }
+    validate_denom(&self.base_denom)?;
+    Ok(())
+  }
+
+  /// Get the full trace, `{path}/{base_denom}`, or the base denom if there is no path.
+  pub fn full_path(&self) -> String {
+    match self.path.is_empty() {
+      true => self.base_denom.clone(),
+      false => format!("{}/{}", self.path, self.base_denom),
+    }
+  }
+
+  /// Get the uppercase hex SHA-256 hash of the full trace.
+  pub fn hash(&self) -> String {
+    hex::encode_upper(Sha256::digest(self.full_path().as_bytes()))
+  }
+
+  /// Get the on-chain denom: `ibc/{hash}`, or the base denom if there is no path.
+  pub fn ibc_denom(&self) -> String {
+    match self.path.is_empty() {
+      true => self.base_denom.clone(),
+      false => format!("ibc/{}", self.hash()),
+    }
+  }
+}
+
+/// Get the hash from an `ibc/{hash}` denom.
+///
+/// Requires the hash to be 64 hex characters.
+pub fn parse_ibc_denom(denom: &str) -> XcosmResult<&str> {
+  match denom.strip_prefix("ibc/") {
+    Some(hash) if hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) => Ok(hash),
+    _ => Err(
+      ValidateError::NotValid {
+        kind: "ibc denom".to_string(),
+        reason: format!(
+          "{}: must be ibc/ followed by a 64 character hex hash",
+          denom
+        ),
+      }
+      .into(),
+    ),
+  }
+}
+
+/// Check an IBC channel identifier, `channel-{n}`.
+fn is_channel_id(id: &str) -> bool {
+  id.strip_prefix("channel-")
+    .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Check an IBC port identifier against the ICS-24 rules.
+fn is_port_id(id: &str) -> bool {
+  (2..=128).contains(&id.len())
+    && id
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || "._+-#[]<>".contains(c))
+}
+
+/// Maximum tokenfactory subdenom length.
+pub const MAX_SUBDENOM_LEN: usize = 44;
+
+/// Tokenfactory denom, `factory/{creator}/{subdenom}`.
+#[cw_serde]
+#[derive(Eq)]
+pub struct TokenfactoryDenom {
+  pub creator: Addr,
+  pub subdenom: String,
+}
+
+impl TokenfactoryDenom {
+  /// Create a new tokenfactory denom.
+  ///
+  /// Requires the subdenom to be at most [`MAX_SUBDENOM_LEN`] characters and the full denom
+  /// to be valid.
+  pub fn new(creator: Addr, subdenom: &str) -> XcosmResult<Self> {
+    if subdenom.len() > MAX_SUBDENOM_LEN {
+      return Err(
+        ValidateError::NotValid {
+          kind: "subdenom".to_string(),
+          reason: format!(
+            "{}: must be at most {} characters",
+            subdenom, MAX_SUBDENOM_LEN
+          ),
+        }
+        .into(),
+      );
+    }
+    let res = Self {
+      creator,
+      subdenom: subdenom.to_string(),
+    };
+    validate_denom(&res.denom())?;
+    Ok(res)
+  }
+
+  /// Parse a `factory/{creator}/{subdenom}` denom. The creator address is not validated.
+  pub fn parse(denom: &str) -> XcosmResult<Self> {
+    validate_denom(denom)?;
+    match denom
+      .strip_prefix("factory/")
+      .and_then(|path| path.split_once('/'))
+    {
+      Some((creator, subdenom)) => Self::new(Addr::unchecked(creator), subdenom),
+      None => Err(
+        ValidateError::NotValid {
+          kind: "denom".to_string(),
+          reason: format!(
+            "{}: factory denom must be factory/{{creator}}/{{subdenom}}",
+            denom
+          ),
+        }
+        .into(),
+      ),
+    }
+  }
+
+  /// Check if the denom is a tokenfactory denom created by `creator`.
+  pub fn is_created_by(denom: &str, creator: &Addr) -> bool {
+    denom
+      .strip_prefix("factory/")
+      .and_then(|path| path.split_once('/'))
+      .is_some_and(|(denom_creator, _)| denom_creator == creator.as_str())
+  }
+
+  /// Get the full denom.
+  pub fn denom(&self) -> String {
+    self.to_string()
+  }
+
+  /// Get a coin of the denom.
+  pub fn coin(&self, amount: impl Into<Uint128>) -> Coin {
+    Coin::new(amount, self.denom())
+  }
+
+  /// Get a [`CoinSet`] holding only the denom.
+  pub fn coin_set(&self, amount: impl Into<Uint128>) -> CoinSet {
+    CoinSet(BTreeMap::from([(self.denom(), amount.into())]))
+  }
+}
+
+impl std::fmt::Display for TokenfactoryDenom {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "factory/{}/{}", self.creator, self.subdenom)
+  }
+}
+
+impl From<TokenfactoryDenom> for Denom {
+  fn from(denom: TokenfactoryDenom) -> Self {
+    Denom::unchecked(denom.denom())
+  }
+}
+
+/// Display metadata for a base denom.
+#[cw_serde]
+#[derive(Eq)]
+pub struct DenomMetadata {
+  /// Display symbol, e.g. `ATOM`.
+  pub symbol: String,
+  /// Decimal places between the base and display units, e.g. 6 for `uatom`.
+  pub exponent: u32,
+  /// Human-readable name, e.g. `Cosmos Hub Atom`.
+  pub name: String,
+}
+
+impl DenomMetadata {
+  pub fn new(symbol: impl Into<String>, exponent: u32, name: impl Into<String>) -> Self {
+    Self {
+      symbol: symbol.into(),
+      exponent,
+      name: name.into(),
+    }
+  }
+
+  /// Format a base amount in display units, e.g. `1500000` as `1.5 ATOM`.
+  pub fn format(&self, amount: Uint128) -> String {
+    let digits = amount.to_string();
+    let exponent = self.exponent as usize;
+    if exponent == 0 {
+      return format!("{} {}", digits, self.symbol);
+    }
+    let digits = format!("{:0>width$}", digits, width = exponent + 1);
+    let (whole, frac) = digits.split_at(digits.len() - exponent);
+    match frac.trim_end_matches('0') {
+      "" => format!("{} {}", whole, self.symbol),
+      frac => format!("{}.{} {}", whole, frac, self.symbol),
+    }
+  }
+}
+
+/// Display metadata by base denom.
+pub type DenomRegistry = BTreeMap<String, DenomMetadata>;
+
+/// Amounts by which coins fall short of an expectation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoinShortfall {
+  /// Expected denoms that are not present, with the expected amounts.
+  pub missing: CoinSet,
+  /// Expected denoms present in too small an amount, with the amounts short.
+  pub short: CoinSet,
+}
+
+impl CoinShortfall {
+  /// Get the shortfall of `held` against a single expected coin.
+  pub fn of_coin(held: Option<Uint128>, expected: &Coin) -> Self {
+    let mut res = CoinShortfall::default();
+    match held {
+      None => {
+        res.missing.insert(expected.denom.clone(), expected.amount);
+      }
+      Some(held) if held < expected.amount => {
+        res
+          .short
+          .insert(expected.denom.clone(), expected.amount - held);
+      }
+      Some(_) => {}
+    }
+    res
+  }
+
+  /// Check if nothing is missing or short.
+  pub fn is_empty(&self) -> bool {
+    self.missing.is_empty() && self.short.is_empty()
+  }
+}
+
+impl std::fmt::Display for CoinShortfall {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let join = |coins: &CoinSet| {
+      coins
+        .iter()
+        .map(|(denom, amount)| format!("{}{}", amount, denom))
+        .collect::<Vec<_>>()
+        .join(",")
+    };
+    write!(
+      f,
+      "missing [{}], short [{}]",
+      join(&self.missing),
+      join(&self.short)
+    )
+  }
+}
+
+/// Whether a [`DenomFilter`] lists allowed or denied denoms.
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum DenomFilterMode {
+  Allow,
+  Deny,
+}
+
+/// Denom allowlist or denylist for incoming coins.
+#[cw_serde]
+pub struct DenomFilter {
+  pub mode: DenomFilterMode,
+  pub denoms: BTreeSet<String>,
+  /// Drop filtered coins rather than rejecting them.
+  pub drop_filtered: bool,
+}
+
+impl DenomFilter {
+  /// Create a filter permitting only `denoms`.
+  pub fn allow(denoms: impl IntoIterator<Item=impl Into<String>>) -> Self {
+    Self {
+      mode: DenomFilterMode::Allow,
+      denoms: denoms.into_iter().map(Into::into).collect(),
+      drop_filtered: false,
+    }
+  }
+
+  /// Create a filter permitting all but `denoms`.
+  pub fn deny(denoms: impl IntoIterator<Item=impl Into<String>>) -> Self {
+    Self {
+      mode: DenomFilterMode::Deny,
+      denoms: denoms.into_iter().map(Into::into).collect(),
+      drop_filtered: false,
+    }
+  }
+
+  /// Drop filtered coins rather than rejecting them.
+  pub fn dropping(mut self) -> Self {
+    self.drop_filtered = true;
+    self
+  }
+
+  /// Check if the filter permits the denom.
+  pub fn permits(&self, denom: &str) -> bool {
+    match self.mode {
+      DenomFilterMode::Allow => self.denoms.contains(denom),
+      DenomFilterMode::Deny => !self.denoms.contains(denom),
+    }
+  }
+
+  /// Apply the filter to coins, dropping filtered coins if configured.
+  ///
+  /// Otherwise requires coins to contain only permitted denoms.
+  pub fn apply(&self, mut coins: CoinSet) -> XcosmResult<CoinSet> {
+    match self.drop_filtered {
+      true => coins.retain_denoms(self),
+      false => coins.expect_only_denoms(self)?,
+    }
+    Ok(coins)
+  }
+}
+
+impl From<BTreeSet<String>> for DenomFilter {
+  /// Create a filter permitting only `denoms`.
+  fn from(denoms: BTreeSet<String>) -> Self {
+    Self {
+      mode: DenomFilterMode::Allow,
+      denoms,
+      drop_filtered: false,
+    }
+  }
+}
+
+/// Handling of zero-amount coins in a [`CoinSet`].
+#[cw_serde]
+#[derive(Copy, Eq, Default)]
+pub enum ZeroPolicy {
+  /// Keep zero amounts.
+  #[default]
+  Keep,
+  /// Drop zero amounts.
+  Strip,
+  /// Error on zero amounts.
+  Reject,
+}
+
 /// Sorted and dupe-checked map of coins that serializes as a list.
-#[derive(Debug, Clone, PartialEq, Eq, Deref, DerefMut)]
+///
+/// Ordering and hashing follow the sorted `(denom, amount)` entries, consistent with
+/// equality.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Deref, DerefMut)]
 pub struct CoinSet(BTreeMap<String, Uint128>);
 
 impl CoinSet {
@@ -63,11 +533,49 @@ impl CoinSet {
     CoinSet(coins)
   }
 
+  /// Create a new [`CoinSet`] from borrowed `Coins`.
+  ///
+  /// `Coins` are already sorted and unique so this cannot fail.
+  pub fn from_coins_ref(coins: &Coins) -> Self {
+    CoinSet(
+      coins
+        .iter()
+        .map(|coin| (coin.denom.clone(), coin.amount))
+        .collect(),
+    )
+  }
+
+  /// Create a new [`CoinSet`] from coins, summing the amounts of duplicate denoms.
+  ///
+  /// Zero-amount coins are kept.
+  pub fn from_iter_summed(coins: impl IntoIterator<Item=Coin>) -> MathResult<Self> {
+    let mut res = CoinSet::default();
+    for coin in coins {
+      res.try_insert_or_add(&coin.denom, coin.amount)?;
+    }
+    Ok(res)
+  }
+
+  /// Sum sets or coins into a single [`CoinSet`] in one pass with checked arithmetic.
+  ///
+  /// Zero-amount coins are kept.
+  pub fn try_sum<T>(items: impl IntoIterator<Item=T>) -> MathResult<Self>
+  where CoinSet: TryPlusMut<T, Error=MathError> {
+    let mut res = CoinSet::default();
+    for item in items {
+      res.try_plus_mut(item)?;
+    }
+    Ok(res)
+  }
+
   /// Insert the amount into the set.
   ///
-  /// Requires the denom to not already be present.
-  pub fn try_insert(&mut self, denom: &String, amount: Uint128) -> XcosmResult<&mut Uint128> {
-    match self.entry(denom.clone()) {
+  /// Requires the denom to not already be present. With the `strict_denoms` feature, also
+  /// requires the denom to pass [`crate::validate_denom`].
+  pub fn try_insert(&mut self, denom: &str, amount: Uint128) -> XcosmResult<&mut Uint128> {
+    #[cfg(feature = "strict_denoms")]
+    crate::validate_denom(denom)?;
+    match self.entry(denom.to_string()) {
       Entry::Occupied(entry) => Err(
         CoinError::DuplicateDenom {
           denom: entry.key().to_string(),
@@ -78,11 +586,196 @@ impl CoinSet {
     }
   }
 
+  /// Insert the amount into the set, or add it to the existing amount for the denom.
+  ///
+  /// Returns the new total for the denom.
+  pub fn try_insert_or_add(&mut self, denom: &str, amount: Uint128) -> MathResult<Uint128> {
+    let total = match self.get_mut(denom) {
+      Some(total) => total,
+      None => self.entry(denom.to_string()).or_default(),
+    };
+    *total = add_amount(denom, *total, amount)?;
+    Ok(*total)
+  }
+
+  /// Require coins to contain at most `max` denoms.
+  pub fn expect_max_denoms(&self, max: usize) -> XcosmResult<&Self> {
+    if self.len() > max {
+      return Err(
+        CoinError::TooManyDenoms {
+          max,
+          count: self.len(),
+        }
+        .into(),
+      );
+    }
+    Ok(self)
+  }
+
+  /// Require coins to contain at most `max` denoms. See [`CoinSet::expect_max_denoms`].
+  pub fn with_max_denoms(self, max: usize) -> XcosmResult<Self> {
+    self.expect_max_denoms(max)?;
+    Ok(self)
+  }
+
+  /// Create [`CoinSet`] from an unsorted `Vec<Coin>`, checking the length before doing any
+  /// other work.
+  ///
+  /// Requires at most `max` coins and no duplicates.
+  pub fn try_from_bounded(raw: Vec<Coin>, max: usize) -> XcosmResult<Self> {
+    if raw.len() > max {
+      return Err(
+        CoinError::TooManyDenoms {
+          max,
+          count: raw.len(),
+        }
+        .into(),
+      );
+    }
+    raw.try_into()
+  }
+
+  /// Insert the amount into the set, handling a zero amount according to `policy`.
+  ///
+  /// Requires the denom to not already be present.
+  pub fn try_insert_with(
+    &mut self,
+    denom: &str,
+    amount: Uint128,
+    policy: ZeroPolicy,
+  ) -> XcosmResult {
+    if amount.is_zero() {
+      match policy {
+        ZeroPolicy::Keep => {}
+        ZeroPolicy::Strip if self.contains_key(denom) => {}
+        ZeroPolicy::Strip => return Ok(()),
+        ZeroPolicy::Reject => {
+          return Err(
+            CoinError::ZeroAmount {
+              denom: denom.to_string(),
+            }
+            .into(),
+          )
+        }
+      }
+    }
+    self.try_insert(denom, amount)?;
+    Ok(())
+  }
+
+  /// Apply `policy` to the zero amounts in the set.
+  pub fn apply_zero_policy(&mut self, policy: ZeroPolicy) -> XcosmResult {
+    match policy {
+      ZeroPolicy::Keep => {}
+      ZeroPolicy::Strip => self.retain(|_, amount| !amount.is_zero()),
+      ZeroPolicy::Reject => {
+        if let Some((denom, _)) = self.iter().find(|(_, amount)| amount.is_zero()) {
+          return Err(
+            CoinError::ZeroAmount {
+              denom: denom.clone(),
+            }
+            .into(),
+          );
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Apply `policy` to the zero amounts in the set. See [`CoinSet::apply_zero_policy`].
+  pub fn with_zero_policy(mut self, policy: ZeroPolicy) -> XcosmResult<Self> {
+    self.apply_zero_policy(policy)?;
+    Ok(self)
+  }
+
+  /// Subtract `other`, then apply `policy` to any zero amounts left in the result.
+  pub fn try_minus_with(&self, other: &CoinSet, policy: ZeroPolicy) -> XcosmResult<CoinSet> {
+    self.try_minus(other)?.with_zero_policy(policy)
+  }
+
+  /// Subtract `other`, flooring each amount at zero instead of erroring on underflow.
+  ///
+  /// Denoms missing from `self` are ignored. With `strip_zeros`, denoms left at zero are
+  /// removed.
+  pub fn saturating_minus(&self, other: &CoinSet, strip_zeros: bool) -> CoinSet {
+    let mut res = self.clone();
+    res.saturating_minus_mut(other, strip_zeros);
+    res
+  }
+
+  /// Subtract `other` in place, flooring each amount at zero instead of erroring on
+  /// underflow.
+  ///
+  /// See [`CoinSet::saturating_minus`].
+  pub fn saturating_minus_mut(&mut self, other: &CoinSet, strip_zeros: bool) {
+    for (denom, amount) in other.iter() {
+      if let Some(self_amount) = self.get_mut(denom) {
+        *self_amount = self_amount.saturating_sub(*amount);
+      }
+    }
+    if strip_zeros {
+      self.retain(|_, amount| !amount.is_zero());
+    }
+  }
+
+  /// Get a copy of the set without zero amounts.
+  pub fn without_zeros(&self) -> CoinSet {
+    CoinSet(
+      self
+        .iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(denom, amount)| (denom.clone(), *amount))
+        .collect(),
+    )
+  }
+
   /// Get a [`Vec<Coin`] from the [`CoinSet`].
   pub fn into_vec(self) -> Vec<Coin> {
     self.into_iter().collect()
   }
 
+  /// Iterate over the coins in the set.
+  pub fn iter_coins(&self) -> <&CoinSet as IntoIterator>::IntoIter {
+    self.into_iter()
+  }
+
+  /// Take all coins out of the set, leaving it empty.
+  pub fn drain(&mut self) -> <CoinSet as IntoIterator>::IntoIter {
+    std::mem::take(self).into_iter()
+  }
+
+  /// Format the coins for display, e.g. `1.5 ATOM, 3uosmo`. Denoms missing from `registry`
+  /// are shown in base units.
+  pub fn display_with(&self, registry: &DenomRegistry) -> String {
+    self
+      .iter()
+      .map(|(denom, amount)| match registry.get(denom) {
+        Some(metadata) => metadata.format(*amount),
+        None => format!("{}{}", amount, denom),
+      })
+      .collect::<Vec<_>>()
+      .join(", ")
+  }
+
+  /// Serialize as JSON, emitting a bare coin object for single-coin sets and a list
+  /// otherwise.
+  pub fn to_json_compact(&self) -> String {
+    let json = match self.len() {
+      1 => serde_json::to_string(&self.into_iter().next()),
+      _ => serde_json::to_string(self),
+    };
+    // coins always serialize
+    json.unwrap()
+  }
+
+  /// Deserialize from JSON accepting either a bare coin object or a list of coins.
+  pub fn from_json_compact(json: &str) -> XcosmResult<Self> {
+    match serde_json::from_str(json).map_err(|_| XcosmError::Parse {})? {
+      CompactCoinSet::One(coin) => vec![coin].try_into(),
+      CompactCoinSet::Many(coins) => coins.try_into(),
+    }
+  }
+
   /// Validate expected coins and return the actual amount of the matching denom if it is
   /// valid, or an error if the denom is not present or less than the expected amount.
   ///
@@ -90,10 +783,11 @@ impl CoinSet {
   pub fn expect_coin(&self, expected: &Coin) -> XcosmResult<&Uint128> {
     self
       .get(&expected.denom)
-      .filter(|&amount| amount >= &expected.amount)
+      .filter(|&amount| *amount >= expected.amount)
       .ok_or_else(|| {
         CoinError::Insufficient {
-          expected: expected.denom.clone(),
+          expected: expected.to_string(),
+          shortfall: CoinShortfall::of_coin(self.get(&expected.denom).copied(), expected),
         }
         .into()
       })
@@ -101,7 +795,7 @@ impl CoinSet {
 
   /// Require coins to contain only the expected denom at exactly the expected amount.
   pub fn expect_coin_exact(&self, expected: &Coin) -> XcosmResult {
-    if self.expect_coin(expected)? != &expected.amount {
+    if *self.expect_coin(expected)? != expected.amount {
       return Err(
         CoinError::NotExact {
           expected: expected.to_string(),
@@ -112,22 +806,188 @@ impl CoinSet {
     Ok(())
   }
 
+  /// Require coins to contain the denom in an amount between `min` and `max`, inclusive.
+  ///
+  /// Requires `min` to not exceed `max`.
+  pub fn expect_coin_in_range(
+    &self,
+    denom: &str,
+    min: Uint128,
+    max: Uint128,
+  ) -> XcosmResult<&Uint128> {
+    if min > max {
+      return Err(
+        ValidateError::NotValid {
+          kind: "bounds".to_string(),
+          reason: format!("{} min {} exceeds max {}", denom, min, max),
+        }
+        .into(),
+      );
+    }
+    let amount = self.expect_coin(&Coin::new(min, denom))?;
+    if *amount > max {
+      return Err(
+        CoinError::Excessive {
+          limit: Coin::new(max, denom).to_string(),
+        }
+        .into(),
+      );
+    }
+    Ok(amount)
+  }
+
+  /// Require coins to fall within `bounds`, e.g. `min..=max`.
+  ///
+  /// Every denom in the lower bound must be present above it, and when there is an upper
+  /// bound every denom in the coins must be below it. Denoms missing from the upper bound
+  /// allow nothing.
+  pub fn expect_coins_within(&self, bounds: impl RangeBounds<CoinSet>) -> XcosmResult {
+    let (min, min_inclusive) = match bounds.start_bound() {
+      Bound::Included(min) => (Some(min), true),
+      Bound::Excluded(min) => (Some(min), false),
+      Bound::Unbounded => (None, true),
+    };
+    for (denom, min_amount) in min.into_iter().flat_map(|min| min.iter()) {
+      let amount = self.get(denom).copied().unwrap_or_default();
+      if amount < *min_amount || (!min_inclusive && amount == *min_amount) {
+        // An excluded bound requires at least one more than the bound itself.
+        let expected = match min_inclusive {
+          true => Coin::new(*min_amount, denom),
+          false => Coin::new(min_amount.saturating_add(Uint128::one()), denom),
+        };
+        return Err(
+          CoinError::Insufficient {
+            expected: expected.to_string(),
+            shortfall: CoinShortfall::of_coin(self.get(denom).copied(), &expected),
+          }
+          .into(),
+        );
+      }
+    }
+    let (max, max_inclusive) = match bounds.end_bound() {
+      Bound::Included(max) => (max, true),
+      Bound::Excluded(max) => (max, false),
+      Bound::Unbounded => return Ok(()),
+    };
+    for (denom, amount) in self.iter() {
+      let max_amount = max.get(denom).copied().unwrap_or_default();
+      if *amount > max_amount || (!max_inclusive && *amount == max_amount) {
+        return Err(
+          CoinError::Excessive {
+            limit: Coin::new(max_amount, denom).to_string(),
+          }
+          .into(),
+        );
+      }
+    }
+    Ok(())
+  }
+
+  /// Check if coins contain the denom in at least the amount.
+  pub fn ge_coin(&self, coin: &Coin) -> bool {
+    self
+      .get(&coin.denom)
+      .is_some_and(|amount| *amount >= coin.amount)
+  }
+
+  /// Check if coins contain the denom at exactly the amount.
+  pub fn eq_coin(&self, coin: &Coin) -> bool {
+    self
+      .get(&coin.denom)
+      .is_some_and(|amount| *amount == coin.amount)
+  }
+
+  /// Check if every amount in the coins is at most the amount in `other`. Missing denoms
+  /// count as zero.
+  pub fn is_subset_of(&self, other: &CoinSet) -> bool {
+    self
+      .iter()
+      .all(|(denom, amount)| *amount <= other.get(denom).copied().unwrap_or_default())
+  }
+
+  /// Check if the coins contain every amount in `other`. Missing denoms count as zero.
+  pub fn covers(&self, other: &CoinSet) -> bool {
+    other.is_subset_of(self)
+  }
+
+  /// Compare amounts per denom, with missing denoms counted as zero.
+  ///
+  /// Returns `None` when some amounts are greater and others are less. Unlike the derived
+  /// [`Ord`], which compares sets lexicographically for use as map keys.
+  pub fn partial_cmp_amounts(&self, other: &CoinSet) -> Option<Ordering> {
+    match (self.is_subset_of(other), self.covers(other)) {
+      (true, true) => Some(Ordering::Equal),
+      (true, false) => Some(Ordering::Less),
+      (false, true) => Some(Ordering::Greater),
+      (false, false) => None,
+    }
+  }
+
+  /// Get the amounts by which the coins fall short of `expected`.
+  pub fn shortfall(&self, expected: &CoinSet) -> CoinShortfall {
+    let mut res = CoinShortfall::default();
+    for (denom, amount) in expected.iter() {
+      match self.get(denom) {
+        None => {
+          res.missing.insert(denom.clone(), *amount);
+        }
+        Some(held) if held < amount => {
+          res.short.insert(denom.clone(), amount - held);
+        }
+        Some(_) => {}
+      }
+    }
+    res
+  }
+
   /// Require coins to contain all the expected denoms in at least the expected amounts.
-  pub fn expect_coins<'a, I>(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult {
-    expected
-      .into_iter()
-      .map(|c| self.expect_coin(&c))
-      .collect::<Result<Vec<_>, _>>()?;
+  ///
+  /// Repeated denoms in `expected` are summed. On failure the error reports every denom that
+  /// falls short.
+  pub fn expect_coins(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult {
+    let expected = CoinSet::from_iter_summed(expected)?;
+    let shortfall = self.shortfall(&expected);
+    if !shortfall.is_empty() {
+      return Err(
+        CoinError::Insufficient {
+          expected: expected
+            .iter()
+            .map(|(denom, amount)| format!("{}{}", amount, denom))
+            .collect::<Vec<_>>()
+            .join(","),
+          shortfall,
+        }
+        .into(),
+      );
+    }
     Ok(())
   }
 
+  /// Require coins to contain all the expected denoms in at least the expected amounts, and
+  /// get the surplus left after paying them. Zero amounts are left out of the change.
+  pub fn expect_coins_with_change(
+    &self,
+    expected: impl IntoIterator<Item=Coin>,
+  ) -> XcosmResult<CoinSet> {
+    let mut change = self.clone();
+    for coin in expected {
+      let amount = *change.expect_coin(&coin)?;
+      change.insert(coin.denom, amount - coin.amount);
+    }
+    Ok(change.without_zeros())
+  }
+
   /// Require coins to contain only the expected denoms at exactly the expected amounts.
+  ///
+  /// Repeated denoms in `expected` are summed.
   pub fn expect_coins_exact(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult {
-    expected
-      .into_iter()
-      .map(|c| self.expect_coin_exact(&c))
-      .collect::<Result<Vec<_>, _>>()?;
-    Ok(())
+    let expected = CoinSet::from_iter_summed(expected)?;
+    for coin in expected.iter_coins() {
+      self.expect_coin_exact(&coin)?;
+    }
+    self
+      .without_zeros()
+      .expect_only_denoms(&DenomFilter::allow(expected.keys()))
   }
 
   /// Require coins to be empty.
@@ -146,25 +1006,284 @@ impl CoinSet {
     Ok(self)
   }
 
+  /// Scale every amount by `ratio`, rounding each result. Zero amounts are kept.
+  pub fn try_mul_decimal(&self, ratio: Decimal, rounding: Rounding) -> MathResult<CoinSet> {
+    self.try_mul_ratio(ratio.numerator(), ratio.denominator(), rounding)
+  }
+
+  /// Scale every amount by a high-precision `ratio`, rounding each result. Zero amounts are
+  /// kept.
+  pub fn try_mul_decimal256(&self, ratio: Decimal256, rounding: Rounding) -> MathResult<CoinSet> {
+    let fractional = Uint256::from(10u128.pow(Decimal256::DECIMAL_PLACES));
+    let mut res = self.clone();
+    for (_, amount) in res.iter_mut() {
+      let scaled = rounding.mul_ratio256((*amount).into(), ratio.atomics(), fractional)?;
+      *amount = Uint128::try_from(scaled).map_err(|_| ContainerError::Overflow {})?;
+    }
+    Ok(res)
+  }
+
+  /// Scale every amount by `numerator / denominator`, rounding each result. Zero amounts are
+  /// kept.
+  pub fn try_mul_ratio(
+    &self,
+    numerator: Uint128,
+    denominator: Uint128,
+    rounding: Rounding,
+  ) -> MathResult<CoinSet> {
+    let mut res = self.clone();
+    for (_, amount) in res.iter_mut() {
+      *amount = rounding.mul_ratio(*amount, numerator, denominator)?;
+    }
+    Ok(res)
+  }
+
+  /// Split every amount into parts proportional to `weights`.
+  ///
+  /// Shares are rounded down, then the leftover units of each denom are handed out one at a
+  /// time to the positive-weight parts in order. Requires weights to sum to more than zero.
+  pub fn split(&self, weights: &[Uint128]) -> MathResult<Vec<CoinSet>> {
+    let total = weights
+      .iter()
+      .try_fold(Uint128::zero(), |total, weight| total.checked_add(*weight))?;
+    if total.is_zero() {
+      return Err(ValueError::DivideByZero {}.into());
+    }
+    let mut parts = vec![CoinSet::default(); weights.len()];
+    for (denom, amount) in self.iter() {
+      let mut rem = *amount;
+      for (part, weight) in parts.iter_mut().zip(weights) {
+        let share = Rounding::Floor.mul_ratio(*amount, *weight, total)?;
+        rem -= share;
+        part.insert(denom.clone(), share);
+      }
+      for (part, _) in parts
+        .iter_mut()
+        .zip(weights)
+        .filter(|(_, weight)| !weight.is_zero())
+        .take(rem.u128() as usize)
+      {
+        if let Some(share) = part.get_mut(denom) {
+          *share += Uint128::one();
+        }
+      }
+    }
+    Ok(parts)
+  }
+
+  /// Check if coins contain the denom.
+  pub fn contains_denom(&self, denom: &str) -> bool {
+    self.contains_key(denom)
+  }
+
+  /// Get the denoms present in either set, with the amounts of shared denoms summed.
+  pub fn union(&self, other: &CoinSet) -> MathResult<CoinSet> {
+    let mut res = self.clone();
+    for (denom, amount) in other.iter() {
+      let res_amount = res.entry(denom.clone()).or_default();
+      *res_amount = add_amount(denom, *res_amount, *amount)?;
+    }
+    Ok(res)
+  }
+
+  /// Get the denoms present in both sets, with the smaller amount of each.
+  pub fn intersection(&self, other: &CoinSet) -> CoinSet {
+    CoinSet(
+      self
+        .iter()
+        .filter_map(|(denom, amount)| {
+          other
+            .get(denom)
+            .map(|other_amount| (denom.clone(), *amount.min(other_amount)))
+        })
+        .collect(),
+    )
+  }
+
+  /// Get the positive amounts remaining after taking `other` away from the coins.
+  ///
+  /// Denoms missing from `other` are kept whole and denoms fully covered by `other` are
+  /// dropped.
+  pub fn difference(&self, other: &CoinSet) -> CoinSet {
+    CoinSet(
+      self
+        .iter()
+        .map(|(denom, amount)| {
+          let other_amount = other.get(denom).copied().unwrap_or_default();
+          (denom.clone(), amount.saturating_sub(other_amount))
+        })
+        .filter(|(_, amount)| !amount.is_zero())
+        .collect(),
+    )
+  }
+
+  /// Clamp each amount to at most the amount in `other`, treating missing denoms as zero.
+  ///
+  /// Keeps the denoms of `self`, e.g. to limit a requested withdrawal to an available
+  /// balance.
+  pub fn min_amounts(&self, other: &CoinSet) -> CoinSet {
+    CoinSet(
+      self
+        .iter()
+        .map(|(denom, amount)| {
+          let other_amount = other.get(denom).copied().unwrap_or_default();
+          (denom.clone(), *amount.min(&other_amount))
+        })
+        .collect(),
+    )
+  }
+
+  /// Get the larger amount of each denom present in either set.
+  pub fn max_amounts(&self, other: &CoinSet) -> CoinSet {
+    let mut res = self.clone();
+    for (denom, amount) in other.iter() {
+      let res_amount = res.entry(denom.clone()).or_default();
+      *res_amount = *amount.max(res_amount);
+    }
+    res
+  }
+
+  /// Keep only the denoms permitted by `filter`, regardless of its drop setting.
+  pub fn retain_denoms(&mut self, filter: &DenomFilter) {
+    self.retain(|denom, _| filter.permits(denom));
+  }
+
+  /// Get a copy of the coins containing only the denoms permitted by `filter`.
+  pub fn filtered(&self, filter: &DenomFilter) -> CoinSet {
+    let mut res = self.clone();
+    res.retain_denoms(filter);
+    res
+  }
+
+  /// Get a copy of the coins containing only tokenfactory denoms created by `creator`.
+  pub fn created_by(&self, creator: &Addr) -> CoinSet {
+    CoinSet(
+      self
+        .iter()
+        .filter(|(denom, _)| TokenfactoryDenom::is_created_by(denom, creator))
+        .map(|(denom, amount)| (denom.clone(), *amount))
+        .collect(),
+    )
+  }
+
+  /// Require coins to contain only the denoms permitted by `filter`, regardless of its drop
+  /// setting.
+  pub fn expect_only_denoms(&self, filter: &DenomFilter) -> XcosmResult {
+    match self.keys().find(|denom| !filter.permits(denom)) {
+      Some(denom) => Err(
+        CoinError::UnexpectedDenom {
+          denom: denom.clone(),
+        }
+        .into(),
+      ),
+      None => Ok(()),
+    }
+  }
+
+  /// Get the amounts that must be added to meet or exceed `target`.
+  ///
+  /// Contains every denom in `target`, with zero where already sufficient.
+  pub fn topup_needed(&self, target: &CoinSet) -> CoinSet {
+    CoinSet(
+      target
+        .iter()
+        .map(|(denom, amount)| {
+          let held = self.get(denom).copied().unwrap_or_default();
+          (denom.clone(), amount.saturating_sub(held))
+        })
+        .collect(),
+    )
+  }
+
+  /// Create a bank send message for the coins. Zero amounts are left out.
+  ///
+  /// Requires at least one non-zero coin.
   pub fn send(&self, to: &Addr) -> XcosmResult<CosmosMsg> {
-    match self.len() {
+    let coins = self.without_zeros();
+    match coins.len() {
       0..1 => Ok(send_coin(
-        self.into_iter().next().ok_or_else(|| CoinError::Empty {})?,
+        coins.into_iter().next().ok_or(CoinError::Empty {})?,
         to,
       )),
-      _ => Ok(send_coins(self.expect_some()?, to)),
+      _ => Ok(send_coins(coins, to)),
     }
   }
 
-  pub fn send_many(&self, from: &Addr, output: Vec<(&Addr, CoinSet)>) -> XcosmResult<CosmosMsg> {
+  /// Create a bank burn message for the coins. Zero amounts are left out.
+  ///
+  /// Requires at least one non-zero coin.
+  pub fn burn(&self) -> XcosmResult<CosmosMsg> {
+    Ok(burn_coins(self.without_zeros().expect_some()?))
+  }
+
+  /// Create messages sending the coins to multiple addresses. See [`send_coins_many`].
+  pub fn send_many(
+    &self,
+    from: &Addr,
+    output: Vec<(&Addr, CoinSet)>,
+  ) -> XcosmResult<Vec<CosmosMsg>> {
     send_coins_many(self, from, output)
   }
+
+  /// Create messages sending the coins to multiple addresses. See [`send_coins_many_with`].
+  pub fn send_many_with(
+    &self,
+    from: &Addr,
+    output: Vec<(&Addr, CoinSet)>,
+    merge_outputs: bool,
+  ) -> XcosmResult<Vec<CosmosMsg>> {
+    send_coins_many_with(self, from, output, merge_outputs)
+  }
+
+  /// Create IBC transfer messages for the coins.
+  ///
+  /// IBC transfers carry a single coin, so a multi-denom set fans out to one message per
+  /// non-zero denom. Requires coins to not be empty.
+  #[cfg(feature = "stargate")]
+  pub fn send_ibc(
+    &self,
+    channel_id: &str,
+    to: &str,
+    timeout: IbcTimeout,
+  ) -> XcosmResult<Vec<CosmosMsg>> {
+    Ok(send_coins_ibc(
+      self.expect_some()?,
+      channel_id,
+      to,
+      timeout,
+      None,
+    ))
+  }
+
+  /// Create IBC transfer messages for the coins with a memo attached to each transfer.
+  /// Chains before CosmWasm 2.0 ignore the memo.
+  ///
+  /// See [`CoinSet::send_ibc`].
+  #[cfg(feature = "stargate")]
+  pub fn send_ibc_with_memo(
+    &self,
+    channel_id: &str,
+    to: &str,
+    timeout: IbcTimeout,
+    memo: &str,
+  ) -> XcosmResult<Vec<CosmosMsg>> {
+    Ok(send_coins_ibc(
+      self.expect_some()?,
+      channel_id,
+      to,
+      timeout,
+      Some(memo.to_string()),
+    ))
+  }
 }
 
-impl Default for CoinSet {
-  /// Create a default (empty) [`CoinSet`].
-  fn default() -> Self {
-    CoinSet(BTreeMap::new())
+impl std::hash::Hash for CoinSet {
+  fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    state.write_usize(self.len());
+    for (denom, amount) in self.iter() {
+      denom.hash(state);
+      amount.u128().hash(state);
+    }
   }
 }
 
@@ -178,10 +1297,85 @@ impl Serialize for CoinSet {
   }
 }
 
+/// Deserializes either a list of coins or a map of denoms to amounts.
+struct CoinSetVisitor;
+
+impl<'de> Visitor<'de> for CoinSetVisitor {
+  type Value = CoinSet;
+
+  fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "a list of coins or a map of denoms to amounts")
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<CoinSet, A::Error> {
+    let mut coins = CoinSet::default();
+    while let Some(coin) = seq.next_element::<Coin>()? {
+      coins
+        .try_insert(&coin.denom, coin.amount)
+        .map_err(de::Error::custom)?;
+    }
+    Ok(coins)
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<CoinSet, A::Error> {
+    let mut coins = CoinSet::default();
+    while let Some((denom, amount)) = map.next_entry::<String, Uint128>()? {
+      coins
+        .try_insert(&denom, amount)
+        .map_err(de::Error::custom)?;
+    }
+    Ok(coins)
+  }
+}
+
 impl<'de> Deserialize<'de> for CoinSet {
+  /// Deserialize from a list of coins or, for compatibility with external state, a map of
+  /// denoms to amounts.
   fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-    let raw: Vec<Coin> = Deserialize::deserialize(deserializer)?;
-    CoinSet::try_from(raw).map_err(serde::de::Error::custom)
+    deserializer.deserialize_any(CoinSetVisitor)
+  }
+}
+
+/// Compact JSON form of a [`CoinSet`]: a bare coin or a list of coins.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CompactCoinSet {
+  One(Coin),
+  Many(Vec<Coin>),
+}
+
+/// Deserialize a [`CoinSet`], dropping zero amounts. Use with
+/// `#[serde(deserialize_with = "xcosm::deserialize_coins_strip_zeros")]`.
+pub fn deserialize_coins_strip_zeros<'de, D: Deserializer<'de>>(
+  deserializer: D,
+) -> Result<CoinSet, D::Error> {
+  CoinSet::deserialize(deserializer)?
+    .with_zero_policy(ZeroPolicy::Strip)
+    .map_err(de::Error::custom)
+}
+
+/// Deserialize a [`CoinSet`], erroring on zero amounts. Use with
+/// `#[serde(deserialize_with = "xcosm::deserialize_coins_reject_zeros")]`.
+pub fn deserialize_coins_reject_zeros<'de, D: Deserializer<'de>>(
+  deserializer: D,
+) -> Result<CoinSet, D::Error> {
+  CoinSet::deserialize(deserializer)?
+    .with_zero_policy(ZeroPolicy::Reject)
+    .map_err(de::Error::custom)
+}
+
+impl schemars::JsonSchema for CoinSet {
+  fn schema_name() -> String {
+    Vec::<Coin>::schema_name()
+  }
+
+  fn is_referenceable() -> bool {
+    Vec::<Coin>::is_referenceable()
+  }
+
+  /// Use the `Vec<Coin>` schema to match the serialized list form.
+  fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    Vec::<Coin>::json_schema(gen)
   }
 }
 
@@ -195,6 +1389,32 @@ impl std::fmt::Display for CoinSet {
   }
 }
 
+impl std::str::FromStr for CoinSet {
+  type Err = XcosmError;
+
+  /// Parse a comma-separated coin list like `"100uatom,250ibc/..."`, as used by the Cosmos
+  /// SDK CLI. An empty string parses to an empty set.
+  ///
+  /// Requires valid denoms and no duplicates.
+  fn from_str(s: &str) -> XcosmResult<Self> {
+    let mut coins = CoinSet::default();
+    for raw in s.split(',').map(str::trim).filter(|raw| !raw.is_empty()) {
+      let invalid = |reason: String| CoinError::Invalid {
+        coin: raw.to_string(),
+        reason,
+      };
+      let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+      let (amount, denom) = raw.split_at(split);
+      let amount = amount
+        .parse::<u128>()
+        .map_err(|err| invalid(format!("amount: {}", err)))?;
+      validate_denom(denom).map_err(|err| invalid(err.to_string()))?;
+      coins.try_insert(denom, amount.into())?;
+    }
+    Ok(coins)
+  }
+}
+
 impl TryFrom<Coins> for CoinSet {
   type Error = XcosmError;
 
@@ -214,24 +1434,36 @@ impl TryFrom<Vec<Coin>> for CoinSet {
   }
 }
 
-impl Into<Coins> for CoinSet {
+impl From<CoinSet> for Coins {
   /// Convert a [`CoinSet`] into a sorted `Coins`.
-  fn into(self) -> Coins {
-    self.into_vec().try_into().unwrap()
+  fn from(coins: CoinSet) -> Self {
+    coins.into_vec().try_into().unwrap()
   }
 }
 
-impl Into<Vec<Coin>> for CoinSet {
+impl From<CoinSet> for Vec<Coin> {
   /// Convert a [`CoinSet`] into a sorted `Vec<Coin>`.
-  fn into(self) -> Vec<Coin> {
-    self.into_iter().collect()
+  fn from(coins: CoinSet) -> Self {
+    coins.into_iter().collect()
   }
 }
 
-impl Into<Vec<Coin>> for &CoinSet {
+impl From<&CoinSet> for Vec<Coin> {
   /// Convert a [`CoinSet`] into a sorted `Vec<Coin>`.
-  fn into(self) -> Vec<Coin> {
-    self.into_iter().collect()
+  fn from(coins: &CoinSet) -> Self {
+    coins.into_iter().collect()
+  }
+}
+
+impl IntoIterator for CoinSet {
+  type Item = Coin;
+  type IntoIter = Map<BTreeMapIntoIter<String, Uint128>, fn((String, Uint128)) -> Coin>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self
+      .0
+      .into_iter()
+      .map(|(denom, amount)| Coin { denom, amount })
   }
 }
 
@@ -280,11 +1512,57 @@ pub fn send_coins(coins: impl Into<Vec<Coin>>, to: &Addr) -> CosmosMsg {
   })
 }
 
+/// Create bank burn message for coins held by the contract.
+pub fn burn_coins(coins: impl Into<Vec<Coin>>) -> CosmosMsg {
+  CosmosMsg::Bank(BankMsg::Burn {
+    amount: coins.into(),
+  })
+}
+
+/// Get the address of a module account, e.g. `distribution`, on the current chain.
+pub fn module_address(api: &dyn Api, module: &str) -> XcosmResult<Addr> {
+  let hash = Sha256::digest(module.as_bytes());
+  Ok(api.addr_humanize(&CanonicalAddr::from(&hash[..20]))?)
+}
+
+/// Create bank send message for multiple coins to a module account.
+pub fn send_coins_to_module(
+  api: &dyn Api,
+  coins: impl Into<Vec<Coin>>,
+  module: &str,
+) -> XcosmResult<CosmosMsg> {
+  Ok(send_coins(coins, &module_address(api, module)?))
+}
+
+/// Create one IBC transfer message per coin.
+#[cfg(feature = "stargate")]
+fn send_coins_ibc(
+  coins: &CoinSet,
+  channel_id: &str,
+  to: &str,
+  timeout: IbcTimeout,
+  memo: Option<String>,
+) -> Vec<CosmosMsg> {
+  coins
+    .into_iter()
+    .filter(|coin| !coin.amount.is_zero())
+    .map(|coin| {
+      CosmosMsg::Ibc(IbcMsg::Transfer {
+        channel_id: channel_id.to_string(),
+        to_address: to.to_string(),
+        amount: coin,
+        timeout: timeout.clone(),
+        memo: memo.clone(),
+      })
+    })
+    .collect()
+}
+
 /// Bank message input or output. See [protobuf definition](https://github.com/cosmos/cosmos-sdk/blob/v0.40.0/proto/cosmos/bank/v1beta1/bank.proto#L27).
 #[cw_serde]
 pub struct BankMsgIo {
-  address: Addr,
-  coins: Vec<Coin>,
+  pub(crate) address: Addr,
+  pub(crate) coins: Vec<Coin>,
 }
 
 impl BankMsgIo {
@@ -300,52 +1578,521 @@ pub struct BankMsgMultiSend {
   pub outputs: Vec<BankMsgIo>,
 }
 
+impl BankMsgMultiSend {
+  /// Encode as `cosmos.bank.v1beta1.MsgMultiSend` protobuf bytes.
+  #[cfg(feature = "protobuf")]
+  pub fn encode_proto(&self) -> Vec<u8> {
+    use prost::Message;
+    let io = |io: &BankMsgIo| crate::proto::BankIo {
+      address: io.address.to_string(),
+      coins: io
+        .coins
+        .iter()
+        .map(|coin| crate::proto::Coin {
+          denom: coin.denom.clone(),
+          amount: coin.amount.to_string(),
+        })
+        .collect(),
+    };
+    crate::proto::MsgMultiSend {
+      inputs: self.inputs.iter().map(io).collect(),
+      outputs: self.outputs.iter().map(io).collect(),
+    }
+    .encode_to_vec()
+  }
+}
+
+/// Require the output coins to add up to exactly `coins`.
+fn check_send_outputs(coins: &CoinSet, to: &[(&Addr, CoinSet)]) -> XcosmResult {
+  let mut rem: CoinSet = coins.clone();
+  for (_, out_coins) in to.iter() {
+    for coin in out_coins.into_iter() {
+      let amount = rem
+        .get_mut(&coin.denom)
+        .ok_or_else(|| CoinError::Insufficient {
+          expected: coin.to_string(),
+          shortfall: CoinShortfall::of_coin(None, &coin),
+        })?;
+      *amount = amount
+        .checked_sub(coin.amount)
+        .map_err(|_| CoinError::Insufficient {
+          expected: coin.to_string(),
+          shortfall: CoinShortfall::of_coin(Some(*amount), &coin),
+        })?;
+    }
+  }
+  if rem.values().any(|amount| !amount.is_zero()) {
+    return Err(CoinError::IoMismatch {}.into());
+  }
+  Ok(())
+}
+
+/// Combine outputs to the same address, keeping the order each address first appears in.
+fn merge_send_outputs(to: Vec<(&Addr, CoinSet)>) -> XcosmResult<Vec<(&Addr, CoinSet)>> {
+  let mut merged: Vec<(&Addr, CoinSet)> = Vec::with_capacity(to.len());
+  for (addr, out_coins) in to {
+    match merged
+      .iter_mut()
+      .find(|(merged_addr, _)| *merged_addr == addr)
+    {
+      Some((_, merged_coins)) => merged_coins.try_plus_mut(&out_coins)?,
+      None => merged.push((addr, out_coins)),
+    }
+  }
+  Ok(merged)
+}
+
+/// Create bank multi-send message for multiple coins to multiple addresses, with one output
+/// per recipient. See [`send_coins_many_with`].
+pub fn send_coins_many(
+  coins: &CoinSet,
+  from: &Addr,
+  to: Vec<(&Addr, CoinSet)>,
+) -> XcosmResult<Vec<CosmosMsg>> {
+  send_coins_many_with(coins, from, to, true)
+}
+
 /// Create bank multi-send message for multiple coins to multiple addresses. Not supported
 /// natively in `cosmwasm_std`; encodes a `/cosmos.bank.v1beta1.MsgMultiSend` as
 /// [`BankMsgMultiSend`] using [`CosmosMsg::Any`]`.
+///
+/// With `merge_outputs`, each recipient gets a single output holding all its coins. Otherwise
+/// every coin gets its own output.
+///
+/// Chains require `Any` payloads to be protobuf, which needs the `protobuf` feature. Without
+/// it the message is encoded as JSON.
+///
+/// Requires the output coins to add up to exactly `coins`.
 #[cfg(feature = "cosmwasm_2_0")]
-pub fn send_coins_many(
+pub fn send_coins_many_with(
   coins: &CoinSet,
   from: &Addr,
   to: Vec<(&Addr, CoinSet)>,
-) -> XcosmResult<CosmosMsg> {
-  let mut rem: CoinSet = coins.clone();
-  let mut outputs: Vec<BankMsgIo> = Vec::with_capacity(to.len());
-  for (addr, out_coins) in to.into_iter() {
-    for coin in out_coins.into_iter() {
-      rem
-        .try_minus_mut(&coin)
-        .map_err(|_| CoinError::Insufficient {
-          expected: coin.to_string(),
-        })?;
-      outputs.push(BankMsgIo {
+  merge_outputs: bool,
+) -> XcosmResult<Vec<CosmosMsg>> {
+  check_send_outputs(coins, &to)?;
+  let outputs: Vec<BankMsgIo> = match merge_outputs {
+    true => merge_send_outputs(to)?
+      .into_iter()
+      .map(|(addr, out_coins)| (addr, out_coins.without_zeros()))
+      .filter(|(_, out_coins)| !out_coins.is_empty())
+      .map(|(addr, out_coins)| BankMsgIo {
         address: addr.clone(),
-        coins: vec![coin],
-      });
-    }
-  }
-  rem.expect_none().map_err(|_| CoinError::IoMismatch {})?;
+        coins: out_coins.into_vec(),
+      })
+      .collect(),
+    false => to
+      .into_iter()
+      .flat_map(|(addr, out_coins)| {
+        out_coins.without_zeros().into_iter().map(|coin| BankMsgIo {
+          address: addr.clone(),
+          coins: vec![coin],
+        })
+      })
+      .collect(),
+  };
   let inputs: Vec<BankMsgIo> = vec![BankMsgIo {
     address: from.clone(),
-    coins: coins.into(),
+    coins: coins.without_zeros().into(),
   }];
-  Ok(CosmosMsg::Any(AnyMsg {
+  let msg = BankMsgMultiSend { inputs, outputs };
+  #[cfg(feature = "protobuf")]
+  let value = msg.encode_proto().into();
+  #[cfg(not(feature = "protobuf"))]
+  let value = cosmwasm_std::to_json_binary(&msg).map_err(|err| CoinError::Unexpected {
+    msg: format!("unable to serialize BankMsgMultiSend: {}", err),
+  })?;
+  Ok(vec![CosmosMsg::Any(AnyMsg {
     type_url: "/cosmos.bank.v1beta1.MsgMultiSend".to_string(),
-    value: to_json_binary(&BankMsgMultiSend { inputs, outputs }).map_err(|err| {
-      CoinError::Unexpected {
-        msg: format!("unable to serialize BankMsgMultiSend: {}", err),
-      }
-    })?,
-  }))
+    value,
+  })])
 }
 
+/// Create bank send messages for multiple coins to multiple addresses, one per output, for
+/// chains without `CosmosMsg::Any` support.
+///
+/// With `merge_outputs`, outputs to the same address are combined into a single send.
+///
+/// Requires the output coins to add up to exactly `coins`.
 #[cfg(not(feature = "cosmwasm_2_0"))]
-pub fn send_coins_many(
-  _coins: &CoinSet,
+pub fn send_coins_many_with(
+  coins: &CoinSet,
   _from: &Addr,
   to: Vec<(&Addr, CoinSet)>,
-) -> CoinResult<CosmosMsg> {
-  to.into_iter()
-    .map(|(addr, out_coins)| send_coins(out_coins.into(), addr))
-    .collect()
+  merge_outputs: bool,
+) -> XcosmResult<Vec<CosmosMsg>> {
+  check_send_outputs(coins, &to)?;
+  let to = match merge_outputs {
+    true => merge_send_outputs(to)?,
+    false => to,
+  };
+  Ok(
+    to.into_iter()
+      .map(|(addr, out_coins)| (addr, out_coins.without_zeros()))
+      .filter(|(_, out_coins)| !out_coins.is_empty())
+      .map(|(addr, out_coins)| send_coins(out_coins, addr))
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{testing::sent_outputs, XcosmError};
+
+  #[test]
+  fn denom_trace_rejects_odd_path() {
+    let trace = DenomTrace::parse("transfer/channel-0/uatom").unwrap();
+    assert!(trace.validate().is_ok());
+    let trace = DenomTrace {
+      path: "transfer/channel-0/transfer".to_string(),
+      base_denom: "uatom".to_string(),
+    };
+    assert!(trace.validate().is_err());
+  }
+
+  #[cfg(feature = "stargate")]
+  fn ibc_transfers(msgs: &[CosmosMsg]) -> Vec<(String, Coin, Option<String>)> {
+    msgs
+      .iter()
+      .map(|msg| match msg {
+        CosmosMsg::Ibc(IbcMsg::Transfer {
+          channel_id,
+          amount,
+          memo,
+          ..
+        }) => (channel_id.clone(), amount.clone(), memo.clone()),
+        msg => panic!("not an IBC transfer: {msg:?}"),
+      })
+      .collect()
+  }
+
+  #[cfg(feature = "stargate")]
+  #[test]
+  fn send_ibc_with_memo_per_denom() {
+    let timeout = IbcTimeout::with_timestamp(cosmwasm_std::Timestamp::from_seconds(100));
+    let memo = Some("note".to_string());
+    let coins: CoinSet = "10uatom".parse().unwrap();
+    let msgs = coins
+      .send_ibc_with_memo("channel-1", "cosmos1to", timeout.clone(), "note")
+      .unwrap();
+    assert_eq!(
+      ibc_transfers(&msgs),
+      vec![(
+        "channel-1".to_string(),
+        Coin::new(10u128, "uatom"),
+        memo.clone()
+      )]
+    );
+    let coins: CoinSet = "10uatom,0ujuno,5uosmo".parse().unwrap();
+    let msgs = coins
+      .send_ibc_with_memo("channel-1", "cosmos1to", timeout.clone(), "note")
+      .unwrap();
+    assert_eq!(
+      ibc_transfers(&msgs),
+      vec![
+        (
+          "channel-1".to_string(),
+          Coin::new(10u128, "uatom"),
+          memo.clone()
+        ),
+        ("channel-1".to_string(), Coin::new(5u128, "uosmo"), memo),
+      ]
+    );
+    assert!(CoinSet::default()
+      .send_ibc_with_memo("channel-1", "cosmos1to", timeout, "note")
+      .is_err());
+  }
+
+  #[test]
+  fn retain_denoms_can_empty_set() {
+    let mut coins: CoinSet = "10uatom,5uosmo".parse().unwrap();
+    coins.retain_denoms(&DenomFilter::allow(["ujuno"]));
+    assert!(coins.is_empty());
+    let coins: CoinSet = "10uatom,5uosmo".parse().unwrap();
+    assert_eq!(
+      coins.filtered(&DenomFilter::deny(["uatom"])),
+      "5uosmo".parse().unwrap()
+    );
+    assert_eq!(
+      DenomFilter::allow(["ujuno"])
+        .dropping()
+        .apply(coins)
+        .unwrap(),
+      CoinSet::default()
+    );
+  }
+
+  #[test]
+  fn expect_only_denoms_rejects_disallowed() {
+    let coins: CoinSet = "10uatom,5uosmo".parse().unwrap();
+    assert!(coins
+      .expect_only_denoms(&DenomFilter::allow(["uatom", "uosmo"]))
+      .is_ok());
+    assert!(matches!(
+      coins.expect_only_denoms(&DenomFilter::allow(["uatom"])),
+      Err(XcosmError::Coin(CoinError::UnexpectedDenom { denom })) if denom == "uosmo"
+    ));
+    assert!(coins
+      .expect_only_denoms(&DenomFilter::deny(["uatom"]))
+      .is_err());
+  }
+
+  #[test]
+  fn compare_against_coin() {
+    let coins: CoinSet = "100uatom".parse().unwrap();
+    // present and sufficient
+    assert!(coins.ge_coin(&Coin::new(99u128, "uatom")));
+    assert!(coins.ge_coin(&Coin::new(100u128, "uatom")));
+    assert!(coins.eq_coin(&Coin::new(100u128, "uatom")));
+    assert!(!coins.eq_coin(&Coin::new(99u128, "uatom")));
+    // present and insufficient
+    assert!(!coins.ge_coin(&Coin::new(101u128, "uatom")));
+    assert!(!coins.eq_coin(&Coin::new(101u128, "uatom")));
+    // absent, even for a zero amount
+    assert!(!coins.ge_coin(&Coin::new(1u128, "uosmo")));
+    assert!(!coins.ge_coin(&Coin::new(0u128, "uosmo")));
+    assert!(!coins.eq_coin(&Coin::new(0u128, "uosmo")));
+  }
+
+  #[test]
+  fn from_coins_ref_keeps_coins() {
+    let coins =
+      Coins::try_from(vec![Coin::new(5u128, "uosmo"), Coin::new(10u128, "uatom")]).unwrap();
+    let set = CoinSet::from_coins_ref(&coins);
+    assert_eq!(set, "10uatom,5uosmo".parse().unwrap());
+    assert_eq!(set, CoinSet::try_from(coins.clone()).unwrap());
+    assert_eq!(
+      CoinSet::from_coins_ref(&Coins::default()),
+      CoinSet::default()
+    );
+  }
+
+  #[test]
+  fn topup_needed_mixed() {
+    let held: CoinSet = "100uatom,5uosmo,7ujuno".parse().unwrap();
+    let target: CoinSet = "60uatom,20uosmo,10ustars".parse().unwrap();
+    // above target, below target and absent
+    assert_eq!(
+      held.topup_needed(&target),
+      "0uatom,15uosmo,10ustars".parse().unwrap()
+    );
+    assert!(held
+      .topup_needed(&"50uatom".parse().unwrap())
+      .without_zeros()
+      .is_empty());
+  }
+
+  #[test]
+  fn compact_json_forms() {
+    let empty = CoinSet::default();
+    assert_eq!(empty.to_json_compact(), "[]");
+    assert_eq!(CoinSet::from_json_compact("[]").unwrap(), empty);
+    let one: CoinSet = "10uatom".parse().unwrap();
+    assert_eq!(one.to_json_compact(), r#"{"denom":"uatom","amount":"10"}"#);
+    assert_eq!(
+      CoinSet::from_json_compact(&one.to_json_compact()).unwrap(),
+      one
+    );
+    assert_eq!(
+      CoinSet::from_json_compact(r#"[{"denom":"uatom","amount":"10"}]"#).unwrap(),
+      one
+    );
+    let two: CoinSet = "10uatom,5uosmo".parse().unwrap();
+    assert_eq!(
+      two.to_json_compact(),
+      r#"[{"denom":"uatom","amount":"10"},{"denom":"uosmo","amount":"5"}]"#
+    );
+    assert_eq!(
+      CoinSet::from_json_compact(&two.to_json_compact()).unwrap(),
+      two
+    );
+    assert!(CoinSet::from_json_compact(r#""10uatom""#).is_err());
+  }
+
+  #[test]
+  fn from_iter_summed_merges_duplicates() {
+    let coins = CoinSet::from_iter_summed([
+      Coin::new(10u128, "uatom"),
+      Coin::new(5u128, "uosmo"),
+      Coin::new(7u128, "uatom"),
+      Coin::new(0u128, "ujuno"),
+    ])
+    .unwrap();
+    assert_eq!(coins, "17uatom,0ujuno,5uosmo".parse().unwrap());
+  }
+
+  #[test]
+  fn from_iter_summed_overflow() {
+    assert!(
+      CoinSet::from_iter_summed([Coin::new(u128::MAX, "uatom"), Coin::new(1u128, "uatom"),])
+        .is_err()
+    );
+  }
+
+  #[test]
+  fn equal_sets_hash_once() {
+    let mut forward = CoinSet::default();
+    forward.try_insert("uatom", Uint128::new(10)).unwrap();
+    forward.try_insert("uosmo", Uint128::new(5)).unwrap();
+    let mut backward = CoinSet::default();
+    backward.try_insert("uosmo", Uint128::new(5)).unwrap();
+    backward.try_insert("uatom", Uint128::new(10)).unwrap();
+    let set = std::collections::HashSet::from([forward.clone(), backward]);
+    assert_eq!(set.len(), 1);
+    assert!(set.contains(&forward));
+    assert!(!set.contains(&"10uatom".parse().unwrap()));
+  }
+
+  #[test]
+  fn set_algebra_empty_and_disjoint() {
+    let coins: CoinSet = "10uatom,5uosmo".parse().unwrap();
+    let empty = CoinSet::default();
+    assert!(coins.intersection(&empty).is_empty());
+    assert!(empty.intersection(&coins).is_empty());
+    assert_eq!(coins.difference(&empty), coins);
+    assert!(empty.difference(&coins).is_empty());
+    let disjoint: CoinSet = "3ujuno".parse().unwrap();
+    assert!(coins.intersection(&disjoint).is_empty());
+    assert_eq!(coins.difference(&disjoint), coins);
+    assert_eq!(disjoint.difference(&coins), disjoint);
+    let overlap: CoinSet = "4uatom,9uosmo".parse().unwrap();
+    assert_eq!(
+      coins.intersection(&overlap),
+      "4uatom,5uosmo".parse().unwrap()
+    );
+    assert_eq!(coins.difference(&overlap), "6uatom".parse().unwrap());
+  }
+
+  #[test]
+  fn deserialize_map_or_array() {
+    let coins: CoinSet = "10uatom,5uosmo".parse().unwrap();
+    let array = r#"[{"denom":"uatom","amount":"10"},{"denom":"uosmo","amount":"5"}]"#;
+    // round trip, always serialized as an array
+    let json = cosmwasm_std::to_json_string(&coins).unwrap();
+    assert_eq!(json, array);
+    assert_eq!(cosmwasm_std::from_json::<CoinSet>(&json).unwrap(), coins);
+    // map in, array out
+    let from_map: CoinSet = cosmwasm_std::from_json(r#"{"uosmo":"5","uatom":"10"}"#).unwrap();
+    assert_eq!(from_map, coins);
+    assert_eq!(cosmwasm_std::to_json_string(&from_map).unwrap(), array);
+    // array in, array out
+    let from_array: CoinSet = cosmwasm_std::from_json(array).unwrap();
+    assert_eq!(cosmwasm_std::to_json_string(&from_array).unwrap(), array);
+    // neither shape
+    assert!(cosmwasm_std::from_json::<CoinSet>(r#""10uatom""#).is_err());
+    assert!(cosmwasm_std::from_json::<CoinSet>("10").is_err());
+    assert!(cosmwasm_std::from_json::<CoinSet>(r#"{"uatom":"ten"}"#).is_err());
+    // duplicate denoms are rejected
+    assert!(cosmwasm_std::from_json::<CoinSet>(
+      r#"[{"denom":"uatom","amount":"1"},{"denom":"uatom","amount":"2"}]"#
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn send_many_outputs() {
+    let from = Addr::unchecked("from");
+    let (a, b) = (Addr::unchecked("a"), Addr::unchecked("b"));
+    let total: CoinSet = "10uatom,5uosmo".parse().unwrap();
+    let to = || {
+      vec![
+        (&a, "4uatom".parse().unwrap()),
+        (&b, "6uatom,2uosmo".parse().unwrap()),
+        (&a, "0uatom,3uosmo".parse().unwrap()),
+      ]
+    };
+    let msgs = send_coins_many(&total, &from, to()).unwrap();
+    assert_eq!(
+      sent_outputs(&msgs),
+      vec![
+        ("a".to_string(), "4uatom,3uosmo".parse().unwrap()),
+        ("b".to_string(), "6uatom,2uosmo".parse().unwrap()),
+      ]
+    );
+    let msgs = send_coins_many_with(&total, &from, to(), false).unwrap();
+    let outputs = sent_outputs(&msgs);
+    assert_eq!(
+      CoinSet::try_sum(outputs.iter().map(|(_, coins)| coins)).unwrap(),
+      total
+    );
+    assert!(outputs
+      .iter()
+      .all(|(addr, coins)| ["a", "b"].contains(&addr.as_str()) && !coins.is_empty()));
+    // a multi-send output per coin, or a bank send per recipient entry
+    #[cfg(feature = "cosmwasm_2_0")]
+    assert_eq!(outputs.len(), 4);
+    #[cfg(not(feature = "cosmwasm_2_0"))]
+    assert_eq!(outputs.len(), 3);
+    // outputs must add up to the input
+    assert!(send_coins_many(&"11uatom,5uosmo".parse().unwrap(), &from, to()).is_err());
+  }
+
+  #[test]
+  fn expect_coin_in_range() {
+    let coins: CoinSet = "5uatom".parse().unwrap();
+    let range = |min: u128, max: u128| {
+      coins.expect_coin_in_range("uatom", Uint128::new(min), Uint128::new(max))
+    };
+    assert_eq!(range(5, 5).unwrap(), &Uint128::new(5));
+    assert_eq!(range(1, 10).unwrap(), &Uint128::new(5));
+    match range(6, 10) {
+      Err(XcosmError::Coin(CoinError::Insufficient { shortfall, .. })) => {
+        assert_eq!(shortfall.short, "1uatom".parse().unwrap());
+      }
+      res => panic!("unexpected {res:?}"),
+    }
+    assert!(matches!(
+      range(1, 4),
+      Err(XcosmError::Coin(CoinError::Excessive { limit })) if limit == "4uatom"
+    ));
+    assert!(matches!(
+      range(10, 1),
+      Err(XcosmError::Validate(ValidateError::NotValid { kind, .. })) if kind == "bounds"
+    ));
+    match coins.expect_coin_in_range("uosmo", Uint128::one(), Uint128::new(2)) {
+      Err(XcosmError::Coin(CoinError::Insufficient { shortfall, .. })) => {
+        assert_eq!(shortfall.missing, "1uosmo".parse().unwrap());
+      }
+      res => panic!("unexpected {res:?}"),
+    }
+  }
+
+  #[test]
+  fn expect_coins_within() {
+    let coins: CoinSet = "5uatom,2uosmo".parse().unwrap();
+    let set = |s: &str| s.parse::<CoinSet>().unwrap();
+    assert!(coins.expect_coins_within(set("5uatom")..).is_ok());
+    assert!(coins
+      .expect_coins_within(set("1uatom")..=set("5uatom,2uosmo"))
+      .is_ok());
+    assert!(coins
+      .expect_coins_within(set("1uatom")..set("6uatom,3uosmo"))
+      .is_ok());
+    assert!(coins.expect_coins_within(..).is_ok());
+    match coins.expect_coins_within((Bound::Excluded(set("5uatom")), Bound::Unbounded)) {
+      Err(XcosmError::Coin(CoinError::Insufficient {
+        expected,
+        shortfall,
+      })) => {
+        assert_eq!(expected, "6uatom");
+        assert_eq!(shortfall.short, set("1uatom"));
+      }
+      res => panic!("unexpected {res:?}"),
+    }
+    match coins.expect_coins_within(set("1ujuno")..) {
+      Err(XcosmError::Coin(CoinError::Insufficient { shortfall, .. })) => {
+        assert_eq!(shortfall.missing, set("1ujuno"));
+      }
+      res => panic!("unexpected {res:?}"),
+    }
+    assert!(matches!(
+      coins.expect_coins_within(..set("6uatom,2uosmo")),
+      Err(XcosmError::Coin(CoinError::Excessive { limit })) if limit == "2uosmo"
+    ));
+    assert!(matches!(
+      coins.expect_coins_within(..=set("6uatom")),
+      Err(XcosmError::Coin(CoinError::Excessive { limit })) if limit == "0uosmo"
+    ));
+  }
 }
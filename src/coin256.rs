@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{CheckedMultiplyFractionError, Coin, Decimal256, Fraction, Uint128, Uint256};
+use derive_deref::{Deref, DerefMut};
+
+use crate::{CoinSet, CoinShortfall, ContainerError, MathError, MathResult, TryMul, ValueError};
+
+/// Coin with a [`Uint256`] amount.
+#[cw_serde]
+#[derive(Eq)]
+pub struct Coin256 {
+  pub denom: String,
+  pub amount: Uint256,
+}
+
+impl Coin256 {
+  pub fn new(amount: impl Into<Uint256>, denom: impl Into<String>) -> Self {
+    Self {
+      denom: denom.into(),
+      amount: amount.into(),
+    }
+  }
+}
+
+impl From<Coin> for Coin256 {
+  fn from(coin: Coin) -> Self {
+    Self::new(coin.amount, coin.denom)
+  }
+}
+
+impl TryFrom<Coin256> for Coin {
+  type Error = MathError;
+
+  /// Convert a [`Coin256`] into a [`Coin`].
+  ///
+  /// Requires the amount to fit in a [`Uint128`].
+  fn try_from(coin: Coin256) -> MathResult<Self> {
+    Ok(Coin::new(narrow(coin.amount)?, coin.denom))
+  }
+}
+
+impl std::fmt::Display for Coin256 {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}{}", self.amount, self.denom)
+  }
+}
+
+fn narrow(amount: Uint256) -> MathResult<Uint128> {
+  Uint128::try_from(amount).map_err(|_| ContainerError::Overflow {}.into())
+}
+
+/// Sorted and dupe-checked coins with [`Uint256`] amounts, for accounting that overflows
+/// [`CoinSet`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deref, DerefMut)]
+pub struct CoinSet256(BTreeMap<String, Uint256>);
+
+coin_set_impls!(CoinSet256, Coin256, Uint256);
+
+impl CoinSet256 {
+  /// Convert into a [`CoinSet`].
+  ///
+  /// Requires every amount to fit in a [`Uint128`].
+  pub fn try_into_coin_set(&self) -> MathResult<CoinSet> {
+    self
+      .iter()
+      .map(|(denom, amount)| Ok((denom.clone(), narrow(*amount)?)))
+      .collect::<MathResult<_>>()
+      .map(CoinSet::new)
+  }
+
+  /// Get the shortfall against a single expected coin, saturating amounts that do not fit in
+  /// a [`Uint128`] at [`Uint128::MAX`].
+  fn shortfall_of(&self, expected: &Coin256) -> CoinShortfall {
+    let mut res = CoinShortfall::default();
+    let (set, amount) = match self.get(&expected.denom) {
+      None => (&mut res.missing, expected.amount),
+      Some(held) if *held < expected.amount => (&mut res.short, expected.amount - *held),
+      Some(_) => return res,
+    };
+    set.insert(
+      expected.denom.clone(),
+      narrow(amount).unwrap_or(Uint128::MAX),
+    );
+    res
+  }
+}
+
+impl From<&CoinSet> for CoinSet256 {
+  fn from(coins: &CoinSet) -> Self {
+    CoinSet256(
+      coins
+        .iter()
+        .map(|(denom, amount)| (denom.clone(), (*amount).into()))
+        .collect(),
+    )
+  }
+}
+
+impl From<CoinSet> for CoinSet256 {
+  fn from(coins: CoinSet) -> Self {
+    (&coins).into()
+  }
+}
+
+impl TryFrom<CoinSet256> for CoinSet {
+  type Error = MathError;
+
+  fn try_from(coins: CoinSet256) -> MathResult<Self> {
+    coins.try_into_coin_set()
+  }
+}
+
+/// Scale every amount in `coins` by `f`, keeping zero amounts.
+fn scale_coin_set_256(
+  coins: &CoinSet256,
+  f: impl Fn(Uint256) -> Result<Uint256, CheckedMultiplyFractionError>,
+) -> MathResult<CoinSet256> {
+  let mut res = coins.clone();
+  for (_, amount) in res.iter_mut() {
+    *amount = f(*amount)?;
+  }
+  Ok(res)
+}
+
+impl TryMul<Decimal256> for CoinSet256 {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_mul_floor(&self, other: Decimal256) -> MathResult<Self> {
+    scale_coin_set_256(self, |amount| amount.checked_mul_floor(other))
+  }
+
+  fn try_mul_ceil(&self, other: Decimal256) -> MathResult<Self> {
+    scale_coin_set_256(self, |amount| amount.checked_mul_ceil(other))
+  }
+}
+
+/// Multiply by an exact `(numerator, denominator)` ratio.
+impl TryMul<(Uint256, Uint256)> for CoinSet256 {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_mul_floor(&self, other: (Uint256, Uint256)) -> MathResult<Self> {
+    if other.denominator().is_zero() {
+      return Err(ValueError::DivideByZero {}.into());
+    }
+    scale_coin_set_256(self, |amount| amount.checked_mul_floor(other))
+  }
+
+  fn try_mul_ceil(&self, other: (Uint256, Uint256)) -> MathResult<Self> {
+    if other.denominator().is_zero() {
+      return Err(ValueError::DivideByZero {}.into());
+    }
+    scale_coin_set_256(self, |amount| amount.checked_mul_ceil(other))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{CoinError, TryPlus, XcosmError};
+
+  fn set(coins: &[(u128, &str)]) -> CoinSet256 {
+    CoinSet256::new(
+      coins
+        .iter()
+        .map(|(amount, denom)| (denom.to_string(), Uint256::from(*amount)))
+        .collect(),
+    )
+  }
+
+  #[test]
+  fn uint256_overflow_boundary() {
+    let coins = set(&[(5, "uatom")]);
+    let max = coins
+      .try_plus(&Coin256::new(Uint256::MAX - Uint256::from(5u8), "uatom"))
+      .unwrap();
+    assert_eq!(max.get("uatom"), Some(&Uint256::MAX));
+    assert!(coins
+      .try_plus(&Coin256::new(Uint256::MAX, "uatom"))
+      .is_err());
+    assert!(max.try_into_coin_set().is_err());
+    assert_eq!(
+      CoinSet::try_from(set(&[(u128::MAX, "uatom")])).unwrap(),
+      CoinSet::new(BTreeMap::from([("uatom".to_string(), Uint128::MAX)]))
+    );
+  }
+
+  #[test]
+  fn expect_coin_shortfall() {
+    let coins = set(&[(5, "uatom")]);
+    assert!(coins.expect_coins(vec![Coin256::new(5u8, "uatom")]).is_ok());
+    match coins.expect_coin(&Coin256::new(7u8, "uatom")) {
+      Err(XcosmError::Coin(CoinError::Insufficient { shortfall, .. })) => {
+        assert_eq!(shortfall.short.get("uatom"), Some(&Uint128::new(2)));
+      }
+      res => panic!("unexpected {res:?}"),
+    }
+    match coins.expect_coin(&Coin256::new(Uint256::MAX, "uosmo")) {
+      Err(XcosmError::Coin(CoinError::Insufficient { shortfall, .. })) => {
+        assert_eq!(shortfall.missing.get("uosmo"), Some(&Uint128::MAX));
+      }
+      res => panic!("unexpected {res:?}"),
+    }
+    match coins.expect_coin(&Coin256::new(Uint256::MAX, "uatom")) {
+      Err(XcosmError::Coin(CoinError::Insufficient { shortfall, .. })) => {
+        assert_eq!(shortfall.short.get("uatom"), Some(&Uint128::MAX));
+      }
+      res => panic!("unexpected {res:?}"),
+    }
+  }
+}
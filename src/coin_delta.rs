@@ -0,0 +1,312 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Coin, Int256, Uint128, Uint256};
+use derive_deref::{Deref, DerefMut};
+
+use crate::{
+  CoinSet, ContainerError, MathError, MathResult, TryMinus, TryMinusMut, TryPlus, TryPlusMut,
+};
+
+/// Signed per-denom amounts for tracking owed vs. held balances in one set.
+///
+/// Positive amounts are held, negative amounts are owed.
+#[cw_serde]
+#[derive(Default, Eq, Deref, DerefMut)]
+pub struct CoinDelta(BTreeMap<String, Int256>);
+
+impl CoinDelta {
+  /// Create a new [`CoinDelta`] from a map.
+  pub fn new(deltas: BTreeMap<String, Int256>) -> Self {
+    CoinDelta(deltas)
+  }
+
+  /// Get the change from `before` to `after`.
+  pub fn between(before: &CoinSet, after: &CoinSet) -> MathResult<Self> {
+    CoinDelta::from(after).try_minus(before)
+  }
+
+  /// Get the held amounts, ignoring owed and zero amounts.
+  pub fn held(&self) -> MathResult<CoinSet> {
+    self.magnitudes(|amount| amount > Int256::zero())
+  }
+
+  /// Get the owed amounts as positive values, ignoring held and zero amounts.
+  pub fn owed(&self) -> MathResult<CoinSet> {
+    self.magnitudes(|amount| amount < Int256::zero())
+  }
+
+  /// Split into held and owed amounts. See [`CoinDelta::held`] and [`CoinDelta::owed`].
+  pub fn split(&self) -> MathResult<(CoinSet, CoinSet)> {
+    Ok((self.held()?, self.owed()?))
+  }
+
+  /// Check if every amount nets to zero.
+  pub fn is_settled(&self) -> bool {
+    self.values().all(|amount| amount.is_zero())
+  }
+
+  /// Get a copy of the delta without zero amounts.
+  pub fn without_zeros(&self) -> CoinDelta {
+    CoinDelta(
+      self
+        .iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(denom, amount)| (denom.clone(), *amount))
+        .collect(),
+    )
+  }
+
+  /// Apply the delta to `balance`, getting the settled balance with zero amounts removed.
+  ///
+  /// Requires `balance` to cover every owed amount.
+  pub fn settle(&self, balance: &CoinSet) -> MathResult<CoinSet> {
+    CoinDelta::from(balance).try_plus(self)?.try_into()
+  }
+
+  fn magnitudes(&self, filter: impl Fn(Int256) -> bool) -> MathResult<CoinSet> {
+    let mut res = CoinSet::default();
+    for (denom, amount) in self.iter().filter(|(_, amount)| filter(**amount)) {
+      res.insert(denom.clone(), narrow(amount.unsigned_abs())?);
+    }
+    Ok(res)
+  }
+}
+
+fn narrow(amount: Uint256) -> MathResult<Uint128> {
+  Uint128::try_from(amount).map_err(|_| ContainerError::Overflow {}.into())
+}
+
+impl From<&CoinSet> for CoinDelta {
+  fn from(coins: &CoinSet) -> Self {
+    CoinDelta(
+      coins
+        .iter()
+        .map(|(denom, amount)| (denom.clone(), Int256::from(*amount)))
+        .collect(),
+    )
+  }
+}
+
+impl From<CoinSet> for CoinDelta {
+  fn from(coins: CoinSet) -> Self {
+    (&coins).into()
+  }
+}
+
+impl TryFrom<CoinDelta> for CoinSet {
+  type Error = MathError;
+
+  /// Convert to a [`CoinSet`] with zero amounts removed.
+  ///
+  /// Requires no amount to be owed.
+  fn try_from(delta: CoinDelta) -> MathResult<Self> {
+    if delta.values().any(|amount| *amount < Int256::zero()) {
+      return Err(ContainerError::Underflow {}.into());
+    }
+    delta.held()
+  }
+}
+
+impl TryPlus<&Coin> for CoinDelta {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_plus(&self, other: &Coin) -> MathResult<Self> {
+    let mut res = self.clone();
+    res.try_plus_mut(other)?;
+    Ok(res)
+  }
+}
+
+impl TryPlus<&CoinSet> for CoinDelta {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_plus(&self, other: &CoinSet) -> MathResult<Self> {
+    let mut res = self.clone();
+    res.try_plus_mut(other)?;
+    Ok(res)
+  }
+}
+
+impl TryPlus<&CoinDelta> for CoinDelta {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_plus(&self, other: &CoinDelta) -> MathResult<Self> {
+    let mut res = self.clone();
+    res.try_plus_mut(other)?;
+    Ok(res)
+  }
+}
+
+impl TryPlusMut<&Coin> for CoinDelta {
+  type Error = MathError;
+
+  fn try_plus_mut(&mut self, other: &Coin) -> MathResult {
+    let amount = self.entry(other.denom.clone()).or_default();
+    *amount = amount.checked_add(other.amount.into())?;
+    Ok(())
+  }
+}
+
+impl TryPlusMut<&CoinSet> for CoinDelta {
+  type Error = MathError;
+
+  fn try_plus_mut(&mut self, other: &CoinSet) -> MathResult {
+    self.try_plus_mut(&CoinDelta::from(other))
+  }
+}
+
+impl TryPlusMut<&CoinDelta> for CoinDelta {
+  type Error = MathError;
+
+  fn try_plus_mut(&mut self, other: &CoinDelta) -> MathResult {
+    for (denom, amount) in other.iter() {
+      let self_amount = self.entry(denom.clone()).or_default();
+      *self_amount = self_amount.checked_add(*amount)?;
+    }
+    Ok(())
+  }
+}
+
+/// Subtraction records missing denoms as owed rather than ignoring them.
+impl TryMinus<&Coin> for CoinDelta {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_minus(&self, other: &Coin) -> MathResult<Self> {
+    let mut res = self.clone();
+    res.try_minus_mut(other)?;
+    Ok(res)
+  }
+}
+
+impl TryMinus<&CoinSet> for CoinDelta {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_minus(&self, other: &CoinSet) -> MathResult<Self> {
+    let mut res = self.clone();
+    res.try_minus_mut(other)?;
+    Ok(res)
+  }
+}
+
+impl TryMinus<&CoinDelta> for CoinDelta {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_minus(&self, other: &CoinDelta) -> MathResult<Self> {
+    let mut res = self.clone();
+    res.try_minus_mut(other)?;
+    Ok(res)
+  }
+}
+
+impl TryMinusMut<&Coin> for CoinDelta {
+  type Error = MathError;
+
+  fn try_minus_mut(&mut self, other: &Coin) -> MathResult {
+    let amount = self.entry(other.denom.clone()).or_default();
+    *amount = amount.checked_sub(other.amount.into())?;
+    Ok(())
+  }
+}
+
+impl TryMinusMut<&CoinSet> for CoinDelta {
+  type Error = MathError;
+
+  fn try_minus_mut(&mut self, other: &CoinSet) -> MathResult {
+    self.try_minus_mut(&CoinDelta::from(other))
+  }
+}
+
+impl TryMinusMut<&CoinDelta> for CoinDelta {
+  type Error = MathError;
+
+  fn try_minus_mut(&mut self, other: &CoinDelta) -> MathResult {
+    for (denom, amount) in other.iter() {
+      let self_amount = self.entry(denom.clone()).or_default();
+      *self_amount = self_amount.checked_sub(*amount)?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn coins(coins: &str) -> CoinSet {
+    coins.parse().unwrap()
+  }
+
+  fn delta(deltas: &[(&str, i128)]) -> CoinDelta {
+    CoinDelta::new(
+      deltas
+        .iter()
+        .map(|(denom, amount)| (denom.to_string(), Int256::from(*amount)))
+        .collect(),
+    )
+  }
+
+  #[test]
+  fn between_one_sided_denoms() {
+    let res = CoinDelta::between(&coins("5uatom,3uosmo"), &coins("7uatom,2ujuno")).unwrap();
+    assert_eq!(res, delta(&[("uatom", 2), ("ujuno", 2), ("uosmo", -3)]));
+    assert_eq!(res.owed().unwrap(), coins("3uosmo"));
+    let res = CoinDelta::between(&coins("4uatom"), &CoinSet::default()).unwrap();
+    assert_eq!(res, delta(&[("uatom", -4)]));
+    assert!(!res.is_settled());
+  }
+
+  #[test]
+  fn held_and_owed() {
+    let res = delta(&[("uatom", 2), ("ujuno", 0), ("uosmo", -3)]);
+    assert_eq!(res.held().unwrap(), coins("2uatom"));
+    assert_eq!(res.owed().unwrap(), coins("3uosmo"));
+    assert_eq!(res.split().unwrap(), (coins("2uatom"), coins("3uosmo")));
+    assert_eq!(res.without_zeros(), delta(&[("uatom", 2), ("uosmo", -3)]));
+    assert!(delta(&[("uatom", 0)]).is_settled());
+  }
+
+  #[test]
+  fn settle() {
+    let res = delta(&[("uatom", 2), ("uosmo", -3)]);
+    assert_eq!(
+      res.settle(&coins("3uosmo,1ujuno")).unwrap(),
+      coins("2uatom,1ujuno")
+    );
+    assert!(matches!(
+      res.settle(&coins("2uosmo")),
+      Err(MathError::Container(ContainerError::Underflow {}))
+    ));
+    assert!(res.settle(&CoinSet::default()).is_err());
+  }
+
+  #[test]
+  fn magnitudes_overflow() {
+    let wide = Int256::from(u128::MAX) + Int256::one();
+    let res = CoinDelta::new(BTreeMap::from([("uatom".to_string(), wide)]));
+    assert!(matches!(
+      res.held(),
+      Err(MathError::Container(ContainerError::Overflow {}))
+    ));
+    assert_eq!(res.owed().unwrap(), CoinSet::default());
+    let res = CoinDelta::new(BTreeMap::from([("uatom".to_string(), -wide)]));
+    assert!(matches!(
+      res.owed(),
+      Err(MathError::Container(ContainerError::Overflow {}))
+    ));
+    let res = delta(&[("uatom", -i128::MAX)]);
+    assert_eq!(
+      res.owed().unwrap(),
+      CoinSet::new(BTreeMap::from([(
+        "uatom".to_string(),
+        Uint128::new(i128::MAX as u128)
+      )]))
+    );
+  }
+}
@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{DecCoin, Decimal256, Uint128, Uint256, Uint512};
+use derive_deref::{Deref, DerefMut};
+
+use crate::{CoinSet, CoinShortfall, ContainerError, MathError, MathResult, TryMul, ValueError};
+
+/// Sorted and dupe-checked coins with [`Decimal256`] amounts, mirroring
+/// `cosmos.base.v1beta1.DecCoin`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deref, DerefMut)]
+pub struct DecCoinSet(BTreeMap<String, Decimal256>);
+
+coin_set_impls!(DecCoinSet, DecCoin, Decimal256);
+
+impl DecCoinSet {
+  /// Split into whole coins and the fractional remainder, truncating each amount.
+  ///
+  /// Requires every whole amount to fit in a [`Uint128`].
+  pub fn truncate(&self) -> MathResult<(CoinSet, DecCoinSet)> {
+    let mut whole = CoinSet::default();
+    let mut rem = DecCoinSet::default();
+    for (denom, amount) in self.iter() {
+      let floor = amount.floor();
+      whole.insert(
+        denom.clone(),
+        Uint128::try_from(floor.to_uint_floor()).map_err(|_| ContainerError::Overflow {})?,
+      );
+      rem.insert(denom.clone(), *amount - floor);
+    }
+    Ok((whole, rem))
+  }
+
+  /// Get the per-share reward index increment from distributing `rewards` over
+  /// `total_shares`, rounding down.
+  pub fn index_increment(rewards: &CoinSet, total_shares: Uint128) -> MathResult<DecCoinSet> {
+    if total_shares.is_zero() {
+      return Err(ValueError::DivideByZero {}.into());
+    }
+    let mut res = DecCoinSet::default();
+    for (denom, amount) in rewards.iter() {
+      res.insert(
+        denom.clone(),
+        Decimal256::checked_from_ratio(*amount, total_shares)
+          .map_err(|_| ContainerError::Overflow {})?,
+      );
+    }
+    Ok(res)
+  }
+
+  /// Get the rewards accrued by `shares` since `checkpoint`, treating `self` as the current
+  /// reward index. Rounds down; use [`DecCoinSet::truncate`] to get whole coins.
+  ///
+  /// Denoms missing from `checkpoint` accrue from zero.
+  pub fn accrued_since(&self, checkpoint: &DecCoinSet, shares: Uint128) -> MathResult<DecCoinSet> {
+    let shares = Decimal256::from_ratio(shares, 1u128);
+    let mut res = DecCoinSet::default();
+    for (denom, index) in self.iter() {
+      let start = checkpoint.get(denom).copied().unwrap_or_default();
+      let delta = index
+        .checked_sub(start)
+        .map_err(|_| ContainerError::Underflow {})?;
+      res.insert(denom.clone(), mul_dec(delta, shares, false)?);
+    }
+    Ok(res)
+  }
+
+  /// Get the shortfall against a single expected coin, rounding amounts up to whole coins and
+  /// saturating those that do not fit in a [`Uint128`] at [`Uint128::MAX`].
+  fn shortfall_of(&self, expected: &DecCoin) -> CoinShortfall {
+    let mut res = CoinShortfall::default();
+    let (set, amount) = match self.get(&expected.denom) {
+      None => (&mut res.missing, expected.amount),
+      Some(held) if *held < expected.amount => (&mut res.short, expected.amount - *held),
+      Some(_) => return res,
+    };
+    set.insert(
+      expected.denom.clone(),
+      Uint128::try_from(amount.to_uint_ceil()).unwrap_or(Uint128::MAX),
+    );
+    res
+  }
+}
+
+impl From<&CoinSet> for DecCoinSet {
+  fn from(coins: &CoinSet) -> Self {
+    DecCoinSet(
+      coins
+        .iter()
+        .map(|(denom, amount)| (denom.clone(), Decimal256::from_ratio(*amount, 1u128)))
+        .collect(),
+    )
+  }
+}
+
+impl From<CoinSet> for DecCoinSet {
+  fn from(coins: CoinSet) -> Self {
+    (&coins).into()
+  }
+}
+
+fn mul_dec(a: Decimal256, b: Decimal256, ceil: bool) -> MathResult<Decimal256> {
+  let fractional = Uint512::from(10u128.pow(Decimal256::DECIMAL_PLACES));
+  let product = a.atomics().full_mul(b.atomics());
+  let (quotient, rem) = (product / fractional, product % fractional);
+  let quotient = match ceil && !rem.is_zero() {
+    true => quotient.checked_add(Uint512::one())?,
+    false => quotient,
+  };
+  Ok(Decimal256::new(
+    Uint256::try_from(quotient).map_err(|_| ContainerError::Overflow {})?,
+  ))
+}
+
+/// Scale every amount in `coins` by `ratio`, keeping zero amounts.
+fn scale_dec_coin_set(coins: &DecCoinSet, ratio: Decimal256, ceil: bool) -> MathResult<DecCoinSet> {
+  let mut res = coins.clone();
+  for (_, amount) in res.iter_mut() {
+    *amount = mul_dec(*amount, ratio, ceil)?;
+  }
+  Ok(res)
+}
+
+impl TryMul<Decimal256> for DecCoinSet {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_mul_floor(&self, other: Decimal256) -> MathResult<Self> {
+    scale_dec_coin_set(self, other, false)
+  }
+
+  fn try_mul_ceil(&self, other: Decimal256) -> MathResult<Self> {
+    scale_dec_coin_set(self, other, true)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use super::*;
+  use crate::{CoinError, XcosmError};
+
+  fn set(coins: &[(&str, &str)]) -> DecCoinSet {
+    DecCoinSet::new(
+      coins
+        .iter()
+        .map(|(amount, denom)| (denom.to_string(), Decimal256::from_str(amount).unwrap()))
+        .collect(),
+    )
+  }
+
+  #[test]
+  fn truncate() {
+    let (whole, rem) = set(&[("2.75", "uatom"), ("0.5", "uosmo"), ("3", "ujuno")])
+      .truncate()
+      .unwrap();
+    assert_eq!(
+      whole,
+      CoinSet::new(BTreeMap::from([
+        ("uatom".to_string(), Uint128::new(2)),
+        ("ujuno".to_string(), Uint128::new(3)),
+        ("uosmo".to_string(), Uint128::zero()),
+      ]))
+    );
+    assert_eq!(
+      rem,
+      set(&[("0.75", "uatom"), ("0.5", "uosmo"), ("0", "ujuno")])
+    );
+    assert!(set(&[("340282366920938463463374607431768211456", "uatom")])
+      .truncate()
+      .is_err());
+  }
+
+  #[test]
+  fn mul_dec_rounding() {
+    let third = Decimal256::from_ratio(1u8, 3u8);
+    let coins = set(&[("0.333333333333333333", "uatom")]);
+    assert_eq!(
+      coins.try_mul_floor(third).unwrap(),
+      set(&[("0.11111111111111111", "uatom")])
+    );
+    assert_eq!(
+      coins.try_mul_ceil(third).unwrap(),
+      set(&[("0.111111111111111111", "uatom")])
+    );
+    assert_eq!(
+      mul_dec(Decimal256::MAX, Decimal256::one(), true).unwrap(),
+      Decimal256::MAX
+    );
+    assert!(mul_dec(Decimal256::MAX, Decimal256::percent(101), false).is_err());
+  }
+
+  #[test]
+  fn expect_coin_shortfall() {
+    let coins = set(&[("1.5", "uatom")]);
+    match coins.expect_coin(&DecCoin::new(Decimal256::from_str("2").unwrap(), "uatom")) {
+      Err(XcosmError::Coin(CoinError::Insufficient {
+        expected,
+        shortfall,
+      })) => {
+        assert_eq!(expected, "2uatom");
+        assert_eq!(shortfall.short.get("uatom"), Some(&Uint128::one()));
+        assert!(shortfall.missing.is_empty());
+      }
+      res => panic!("unexpected {res:?}"),
+    }
+    match coins.expect_coin(&DecCoin::new(Decimal256::from_str("2.5").unwrap(), "uosmo")) {
+      Err(XcosmError::Coin(CoinError::Insufficient { shortfall, .. })) => {
+        assert_eq!(shortfall.missing.get("uosmo"), Some(&Uint128::new(3)));
+        assert!(shortfall.short.is_empty());
+      }
+      res => panic!("unexpected {res:?}"),
+    }
+    match coins.expect_coin(&DecCoin::new(Decimal256::MAX, "uosmo")) {
+      Err(XcosmError::Coin(CoinError::Insufficient { shortfall, .. })) => {
+        assert_eq!(shortfall.missing.get("uosmo"), Some(&Uint128::MAX));
+      }
+      res => panic!("unexpected {res:?}"),
+    }
+  }
+}
@@ -5,6 +5,29 @@ use crate::{AuthError, CoinError, FundError, MathError, ValidateError};
 /// Type alias for `std::result::Result` with contract defaults.
 pub type XcosmResult<T=(), E=XcosmError> = std::result::Result<T, E>;
 
+/// Former name of [`XcosmResult`].
+///
+/// The former names are aliases, so code using them works with the new ones:
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use xcosm::{AuthError, CosmixError, CosmixResult, XcosmError, XcosmResult};
+///
+/// fn authorize() -> CosmixResult<u8> {
+///   Err(AuthError::Unauthorized {}.into())
+/// }
+///
+/// let res: XcosmResult<u8> = authorize();
+/// let err: CosmixError = res.unwrap_err();
+/// assert!(matches!(err, XcosmError::Auth(AuthError::Unauthorized {})));
+/// ```
+#[deprecated(note = "renamed to `XcosmResult`")]
+pub type CosmixResult<T=(), E=XcosmError> = XcosmResult<T, E>;
+
+/// Former name of [`XcosmError`].
+#[deprecated(note = "renamed to `XcosmError`")]
+pub type CosmixError = XcosmError;
+
 /// Contract error.
 #[derive(thiserror::Error, Debug, miette::Diagnostic)]
 pub enum XcosmError {
@@ -41,12 +64,12 @@ pub enum XcosmError {
   Parse {},
 }
 
-impl Into<StdError> for XcosmError {
+impl From<XcosmError> for StdError {
   /// Convert contract error into CosmWasm standard error.
-  fn into(self) -> StdError {
-    match self {
+  fn from(err: XcosmError) -> Self {
+    match err {
       XcosmError::Std(err) => err,
-      _ => StdError::generic_err(self.to_string()),
+      _ => StdError::generic_err(err.to_string()),
     }
   }
 }
@@ -1,13 +1,20 @@
-use std::collections::{hash_map::Entry, HashMap};
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet};
+use std::ops::{Deref, DerefMut};
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Api, Coin, CosmosMsg, MessageInfo};
-use derive_deref::{Deref, DerefMut};
+#[cfg(feature = "staking")]
+use cosmwasm_std::StakingMsg;
+use cosmwasm_std::{
+  Addr, Api, BlockInfo, Coin, CosmosMsg, CustomQuery, Decimal, Decimal256, Env, Event, MessageInfo,
+  QuerierWrapper, Timestamp, Uint128, Uint256,
+};
 
 use crate::{
-  math::{ContainerError, TryMinusMut, TryPlusMut, ValueError},
+  asset::{burn_cw20, send_cw20},
+  math::{ContainerError, Rounding, TryMinus, TryMinusMut, TryMul, TryPlusMut, ValueError},
   validate::ApiValidator,
-  CoinError, CoinSet, IntoResult, MathError, ValidateError, Validator, XcosmError, XcosmResult,
+  Authorized, CoinError, CoinSet, DecCoinSet, DenomFilter, Expiration, IntoResult, MathError,
+  MathResult, ValidateError, Validator, XcosmError, XcosmResult,
 };
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
@@ -21,66 +28,445 @@ pub enum FundError {
   #[error("Data validation error during fund operation: {0}")]
   Validate(#[from] ValidateError),
 
+  #[error("Basis points out of range: {bps} > {max}", max = MAX_BPS)]
+  BpsOutOfRange { bps: u32 },
+
   #[error("Fund distribution claims cannot exceed 100%")]
   DistributionOverclaimed {},
 
   #[error("Fund distribution must contain at least one claim")]
   DistributionUnclaimed {},
 
+  #[error("Fund distribution claims must total 100%, got {total}")]
+  DistributionIncomplete { total: Bps },
+
+  #[error("Fund distribution claim for {addr} is zero")]
+  DistributionZeroClaim { addr: String },
+
+  #[error("Fund distribution has {count} claimants, more than the limit of {max}")]
+  DistributionTooManyClaims { max: usize, count: usize },
+
+  #[error("Escrow cannot be released until {expiration}")]
+  EscrowLocked { expiration: Expiration },
+
+  #[error("Escrow can no longer be released, {expiration} has passed")]
+  EscrowExpired { expiration: Expiration },
+
+  #[error("Escrow cannot be refunded yet")]
+  EscrowNotRefundable {},
+
+  #[error("Budget exceeded, remaining this period: {remaining}")]
+  BudgetExceeded { remaining: String },
+
+  #[error("Fee tier thresholds must be strictly increasing, got {threshold} after {prev}")]
+  FeeTiersUnordered { prev: Uint128, threshold: Uint128 },
+
+  #[error("Delegating a distribution claim requires the staking feature")]
+  StakingDisabled {},
+
   #[error("Unexpected fund error: {msg:?}")]
   Unexpected { msg: String },
 }
 
+/// Coins allocated to each recipient of a distribution.
+pub type Allocation = Vec<(Addr, CoinSet)>;
+
+/// Token amounts allocated to each recipient of a cw20 distribution.
+pub type TokenAllocation = Vec<(Addr, Uint128)>;
+
+/// Type of the event built by [`distribution_event`].
+pub const DISTRIBUTION_EVENT_TYPE: &str = "distribution";
+
+/// Basis points making up 100% of a distribution.
+pub const MAX_BPS: u32 = 10000;
+
+/// Basis points, where [`MAX_BPS`] is 100%.
+#[cw_serde]
+#[derive(Copy, Default, Eq, PartialOrd, Ord)]
+pub struct Bps(u32);
+
+impl Bps {
+  pub const ZERO: Bps = Bps(0);
+  pub const MAX: Bps = Bps(MAX_BPS);
+
+  /// Create a new [`Bps`].
+  ///
+  /// Requires `bps` to not exceed [`MAX_BPS`].
+  pub fn new(bps: u32) -> XcosmResult<Self> {
+    if bps > MAX_BPS {
+      return Err(FundError::BpsOutOfRange { bps }.into());
+    }
+    Ok(Self(bps))
+  }
+
+  /// Create a new [`Bps`] that may exceed 100%, e.g. for multipliers.
+  pub const fn unbounded(bps: u32) -> Self {
+    Self(bps)
+  }
+
+  pub const fn u32(&self) -> u32 {
+    self.0
+  }
+
+  pub fn is_zero(&self) -> bool {
+    self.0 == 0
+  }
+
+  /// Require the value to not exceed [`MAX_BPS`].
+  pub fn expect_bounded(self) -> XcosmResult<Self> {
+    Self::new(self.0)
+  }
+
+  pub fn checked_add(self, other: Bps) -> MathResult<Self> {
+    self
+      .0
+      .checked_add(other.0)
+      .map(Self)
+      .ok_or_else(|| ContainerError::Overflow {}.into())
+  }
+
+  pub fn checked_sub(self, other: Bps) -> MathResult<Self> {
+    self
+      .0
+      .checked_sub(other.0)
+      .map(Self)
+      .ok_or_else(|| ContainerError::Underflow {}.into())
+  }
+
+  /// Multiply `amount` by the basis points with the given rounding.
+  pub fn apply(&self, amount: Uint128, rounding: Rounding) -> MathResult<Uint128> {
+    rounding.mul_ratio(amount, Uint128::from(self.0), Uint128::from(MAX_BPS))
+  }
+
+  pub fn to_decimal(&self) -> Decimal {
+    Decimal::from_ratio(self.0, MAX_BPS)
+  }
+}
+
+impl From<Bps> for Decimal {
+  fn from(bps: Bps) -> Self {
+    bps.to_decimal()
+  }
+}
+
+impl std::fmt::Display for Bps {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}bps", self.0)
+  }
+}
+
 #[cw_serde]
 #[derive(Copy)]
-pub struct Claim(u32);
+pub struct Claim(Bps);
 
 impl Claim {
-  pub fn bps(&self) -> u32 {
+  pub fn new(bps: Bps) -> Self {
+    Self(bps)
+  }
+
+  pub fn bps(&self) -> Bps {
     self.0
   }
 
+  /// Claim a share of every amount in `funds`, rounding down.
   pub fn claim(&self, funds: &CoinSet) -> XcosmResult<CoinSet> {
+    self.claim_with(funds, Rounding::Floor)
+  }
+
+  /// Claim a share of every amount in `funds` with the given rounding.
+  pub fn claim_with(&self, funds: &CoinSet, rounding: Rounding) -> XcosmResult<CoinSet> {
     let mut claimed = funds.clone();
     for (_, amount) in claimed.iter_mut() {
-      *amount = self.claim_amount(amount.u128())?.into();
+      *amount = self.claim_amount_with(amount.u128(), rounding)?.into();
     }
     Ok(claimed)
   }
 
+  /// Claim a share of `total`, rounding down.
   pub fn claim_amount(&self, total: u128) -> XcosmResult<u128> {
-    total
-      .checked_mul(self.bps() as u128)
-      .ok_or(MathError::Container(ContainerError::Overflow {}))?
-      .checked_div(100000u128)
-      .ok_or(MathError::Value(ValueError::DivideByZero {}))
+    self.claim_amount_with(total, Rounding::Floor)
+  }
+
+  /// Claim a share of `total` with the given rounding.
+  pub fn claim_amount_with(&self, total: u128, rounding: Rounding) -> XcosmResult<u128> {
+    self
+      .bps()
+      .apply(Uint128::new(total), rounding)
+      .map(|amount| amount.u128())
       .into_result()
   }
 }
 
+/// Recipient of the funds left over after every claim takes its rounded-down share.
+#[cw_serde]
+#[derive(Default)]
+pub enum RemainderPolicy {
+  /// Give the remainder to the first claim by address.
+  #[default]
+  FirstClaim,
+  /// Give the remainder to the claim with the most bps, preferring the first by address on
+  /// ties.
+  LargestClaim,
+  /// Give the remainder to an address, which need not hold a claim.
+  SpecificAddress(Addr),
+  /// Burn the remainder.
+  Burn,
+  /// Split all funds proportionally to the claims, handing leftover units out one at a time.
+  Redistribute,
+}
+
+/// How a claimant's share of a [`Distribution`] is paid out.
+#[cw_serde]
+#[derive(Default)]
+pub enum ClaimTarget {
+  /// Sent to the claimant.
+  #[default]
+  Bank,
+  /// Delegated to `validator` by the distributing contract, with one delegation per denom.
+  /// Only the bond denom should be distributed to the claimant. Paying out requires the
+  /// `staking` feature.
+  Delegate { validator: String },
+}
+
+/// Checks applied by [`Distribution::validate`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DistributionValidation {
+  /// Allow claims and overrides of zero bps.
+  pub allow_zero_claims: bool,
+  /// Maximum number of claimants, including override-only addresses.
+  pub max_claims: Option<usize>,
+  /// Require the claims, and the claims of every overridden denom, to total exactly 100%.
+  pub require_full: bool,
+}
+
+/// Distribution by basis point claims, ordered by address so that iteration, serialization
+/// and generated messages are deterministic.
+///
+/// Also deserializes from the earlier format of a bare map of claims, e.g.
+/// `{"addr": 5000}`, so stored distributions need no migration.
+#[cw_serde]
+#[serde(from = "DistributionJson<Addr, NestedDistribution>")]
+pub struct Distribution {
+  pub claims: BTreeMap<Addr, Claim>,
+  /// Per-denom bps replacing an address's claim for the listed denoms.
+  #[serde(default)]
+  pub overrides: BTreeMap<Addr, BTreeMap<String, Bps>>,
+  #[serde(default)]
+  pub remainder: RemainderPolicy,
+  /// Claims routed through another distribution, e.g. a team splitting its share among
+  /// members.
+  #[serde(default)]
+  pub nested: Vec<NestedDistribution>,
+  /// Payout targets of claimants not paid with a bank send.
+  #[serde(default)]
+  pub targets: BTreeMap<Addr, ClaimTarget>,
+  /// Minimum amount of each denom paid to a claimant, with smaller claims left to the
+  /// remainder policy.
+  #[serde(default)]
+  pub min_payout: CoinSet,
+}
+
+/// A claim on a [`Distribution`] paid out through another distribution rather than to a
+/// single address.
 #[cw_serde]
-#[derive(Deref, DerefMut)]
-pub struct Distribution(HashMap<Addr, Claim>);
+pub struct NestedDistribution {
+  pub claim: Claim,
+  pub distribution: Distribution,
+}
+
+/// Recipient of a share when allocating a [`Distribution`], sorting addresses before nested
+/// distributions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Recipient<'a> {
+  Addr(&'a Addr),
+  Nested(usize),
+}
+
+impl<'a> From<&'a Addr> for Recipient<'a> {
+  fn from(addr: &'a Addr) -> Self {
+    Recipient::Addr(addr)
+  }
+}
 
 impl Distribution {
-  pub fn new(claims: HashMap<Addr, Claim>) -> Self {
-    Distribution(claims)
+  pub fn new(claims: BTreeMap<Addr, Claim>) -> Self {
+    Self {
+      claims,
+      overrides: BTreeMap::new(),
+      remainder: RemainderPolicy::default(),
+      nested: Vec::new(),
+      targets: BTreeMap::new(),
+      min_payout: CoinSet::default(),
+    }
+  }
+
+  /// Skip claims of less than `min_payout` of a denom, handling their coins with the
+  /// remainder policy instead.
+  ///
+  /// The minimum applies to claimants of this distribution, not of nested distributions. If
+  /// every claim of a denom falls short, the denom is paid out as usual.
+  pub fn with_min_payout(mut self, min_payout: CoinSet) -> Self {
+    self.min_payout = min_payout;
+    self
+  }
+
+  /// Pay out the share of `addr` to `target` instead of sending it to the address.
+  pub fn with_target(mut self, addr: Addr, target: ClaimTarget) -> Self {
+    self.targets.insert(addr, target);
+    self
+  }
+
+  /// Get the payout target of `addr`, falling back to the targets of nested distributions.
+  fn target_for(&self, addr: &Addr) -> Option<&ClaimTarget> {
+    self.targets.get(addr).or_else(|| {
+      self
+        .nested
+        .iter()
+        .find_map(|nested| nested.distribution.target_for(addr))
+    })
+  }
+
+  /// Route `claim` of the funds through `distribution`, flattened into the outer allocation
+  /// when distributing. Overrides do not apply to nested claims.
+  pub fn with_nested(mut self, claim: Claim, distribution: Distribution) -> Self {
+    self.nested.push(NestedDistribution {
+      claim,
+      distribution,
+    });
+    self
+  }
+
+  /// Claim `bps` of `denom` for `addr` instead of its default claim. The address does not
+  /// need a default claim.
+  pub fn with_override(mut self, addr: Addr, denom: impl Into<String>, bps: Bps) -> Self {
+    self
+      .overrides
+      .entry(addr)
+      .or_default()
+      .insert(denom.into(), bps);
+    self
+  }
+
+  /// Get the bps of `denom` claimed by `addr`, falling back to its default claim.
+  pub fn bps_for(&self, addr: &Addr, denom: &str) -> Bps {
+    self
+      .overrides
+      .get(addr)
+      .and_then(|denoms| denoms.get(denom))
+      .copied()
+      .or_else(|| self.claims.get(addr).map(Claim::bps))
+      .unwrap_or_default()
+  }
+
+  /// Get the total bps claimed of `denom`, including overrides and nested claims.
+  pub fn total_bps_for(&self, denom: &str) -> XcosmResult<Bps> {
+    let total = self
+      .claimants()
+      .map(|addr| self.bps_for(addr, denom))
+      .chain(self.nested.iter().map(|nested| nested.claim.bps()))
+      .try_fold(Bps::ZERO, |total, bps| total.checked_add(bps))?;
+    if total > Bps::MAX {
+      return Err(FundError::DistributionOverclaimed {}.into());
+    }
+    Ok(total)
+  }
+
+  /// Addresses with a default claim or an override.
+  fn claimants(&self) -> impl Iterator<Item=&Addr> {
+    self.claims.keys().chain(
+      self
+        .overrides
+        .keys()
+        .filter(|addr| !self.claims.contains_key(*addr)),
+    )
+  }
+
+  /// Set the policy for funds left over after rounding.
+  pub fn with_remainder_policy(mut self, remainder: RemainderPolicy) -> Self {
+    self.remainder = remainder;
+    self
+  }
+
+  /// Validate the distribution up front rather than at distribution time.
+  ///
+  /// Requires at least one claim, totals of at most 100% for the claims and every overridden
+  /// denom, valid claimant and remainder addresses, and the checks in `opts`. Nested
+  /// distributions are validated with the same checks.
+  pub fn validate(&self, api: &dyn Api, opts: &DistributionValidation) -> XcosmResult {
+    let count = self.claimants().count() + self.nested.len();
+    if count == 0 {
+      return Err(FundError::DistributionUnclaimed {}.into());
+    }
+    if let Some(max) = opts.max_claims.filter(|max| count > *max) {
+      return Err(FundError::DistributionTooManyClaims { max, count }.into());
+    }
+    for addr in self.claimants() {
+      let _: Addr = api.validate(&addr.as_str())?;
+    }
+    let _: RemainderPolicy = api.validate(&self.remainder)?;
+    if !opts.allow_zero_claims {
+      let zero_claim = self
+        .claims
+        .iter()
+        .find(|(_, claim)| claim.bps().is_zero())
+        .map(|(addr, _)| addr)
+        .or_else(|| {
+          self
+            .overrides
+            .iter()
+            .find(|(_, denoms)| denoms.values().any(Bps::is_zero))
+            .map(|(addr, _)| addr)
+        })
+        .map(Addr::to_string)
+        .or_else(|| {
+          self
+            .nested
+            .iter()
+            .position(|nested| nested.claim.bps().is_zero())
+            .map(|i| format!("nested distribution {i}"))
+        });
+      if let Some(addr) = zero_claim {
+        return Err(FundError::DistributionZeroClaim { addr }.into());
+      }
+    }
+    for nested in self.nested.iter() {
+      nested.distribution.validate(api, opts)?;
+    }
+    let denoms: BTreeSet<&String> = self
+      .overrides
+      .values()
+      .flat_map(|denoms| denoms.keys())
+      .collect();
+    for total in std::iter::once(self.total_bps())
+      .chain(denoms.into_iter().map(|denom| self.total_bps_for(denom)))
+    {
+      let total = total?;
+      if opts.require_full && total != Bps::MAX {
+        return Err(FundError::DistributionIncomplete { total }.into());
+      }
+    }
+    Ok(())
   }
 
-  pub fn claims(&self) -> &HashMap<Addr, Claim> {
-    &self.0
+  pub fn claims(&self) -> &BTreeMap<Addr, Claim> {
+    &self.claims
   }
 
-  pub fn total_bps(&self) -> XcosmResult<u32> {
-    let total = self.claims().iter().map(|(_, claim)| claim.bps()).sum();
-    if total > 10000 {
+  /// Get the total bps claimed, including nested claims.
+  pub fn total_bps(&self) -> XcosmResult<Bps> {
+    let total = self
+      .claims()
+      .values()
+      .chain(self.nested.iter().map(|nested| &nested.claim))
+      .try_fold(Bps::ZERO, |total, claim| total.checked_add(claim.bps()))?;
+    if total > Bps::MAX {
       return Err(FundError::DistributionOverclaimed {}.into());
     }
     Ok(total)
   }
 
   pub fn with_remainder_to(&self, addr: Addr) -> XcosmResult<Self> {
-    let rem_claim = Claim(10000 - self.total_bps()?);
+    let rem_claim = Claim(Bps::MAX.checked_sub(self.total_bps()?)?);
     let mut claims = self.claims().clone();
     match claims.entry(addr) {
       Entry::Vacant(entry) => {
@@ -88,97 +474,1984 @@ impl Distribution {
       }
       Entry::Occupied(mut entry) => {
         let claim = entry.get_mut();
-        claim.0 += rem_claim.bps();
+        claim.0 = claim.0.checked_add(rem_claim.bps())?;
       }
     }
-    Ok(Self(claims))
+    Ok(Self {
+      claims,
+      overrides: self.overrides.clone(),
+      remainder: self.remainder.clone(),
+      nested: self.nested.clone(),
+      targets: self.targets.clone(),
+      min_payout: self.min_payout.clone(),
+    })
   }
 
-  pub fn distribute_coins(&self, from: &Addr, funds: &CoinSet) -> XcosmResult<CosmosMsg> {
-    if self.claims().len() == 0 {
-      return Err(FundError::DistributionUnclaimed {}.into());
+  /// Shares of each claimant and nested distribution in bps, using the overrides for `denom`
+  /// if given.
+  fn shares(&self, denom: Option<&str>) -> Vec<(Recipient<'_>, Uint128)> {
+    self
+      .claimants()
+      .map(|addr| {
+        let bps = match denom {
+          Some(denom) => self.bps_for(addr, denom),
+          None => self.claims.get(addr).map(Claim::bps).unwrap_or_default(),
+        };
+        (Recipient::Addr(addr), bps)
+      })
+      .chain(
+        self
+          .nested
+          .iter()
+          .enumerate()
+          .map(|(i, nested)| (Recipient::Nested(i), nested.claim.bps())),
+      )
+      .map(|(recipient, bps)| (recipient, Uint128::from(bps.u32())))
+      .collect()
+  }
+
+  /// Estimate the number of multi-send outputs distributing funds would produce, e.g. to
+  /// check against a limit before building messages.
+  ///
+  /// Counts each bank recipient once, since outputs are merged per recipient, along with a
+  /// [`RemainderPolicy::SpecificAddress`] recipient. This is exact unless funds round down
+  /// to zero or below the minimum payout for some recipient, and never an underestimate.
+  pub fn estimate_outputs(&self) -> usize {
+    let mut recipients = BTreeSet::new();
+    self.collect_recipients(&mut recipients);
+    recipients
+      .into_iter()
+      .filter(|addr| {
+        self
+          .target_for(addr)
+          .map_or(true, |target| *target == ClaimTarget::Bank)
+      })
+      .count()
+  }
+
+  /// Collect every address that can be paid, including through nested distributions.
+  fn collect_recipients<'a>(&'a self, recipients: &mut BTreeSet<&'a Addr>) {
+    recipients.extend(self.claimants().filter(|addr| {
+      self
+        .claims
+        .get(*addr)
+        .is_some_and(|claim| !claim.bps().is_zero())
+        || self
+          .overrides
+          .get(*addr)
+          .is_some_and(|denoms| denoms.values().any(|bps| !bps.is_zero()))
+    }));
+    if let RemainderPolicy::SpecificAddress(addr) = &self.remainder {
+      recipients.insert(addr);
     }
-    let mut rem = funds.clone();
-    let mut claimed = self
-      .claims()
+    for nested in &self.nested {
+      if !nested.claim.bps().is_zero() {
+        nested.distribution.collect_recipients(recipients);
+      }
+    }
+  }
+
+  /// Compute the exact per-recipient allocation of `funds`, sorted by address.
+  ///
+  /// Zero-bps claims are skipped and any remainder is handled by the [`RemainderPolicy`].
+  /// Burned remainders are left out, see
+  /// [`Distribution::compute_distribution_with_remainder`].
+  pub fn compute_distribution(&self, funds: &CoinSet) -> XcosmResult<Allocation> {
+    self
+      .compute_distribution_with_remainder(funds)
+      .map(|(allocation, _)| allocation)
+  }
+
+  /// Compute the allocation of `funds` along with the remainder left unallocated, which is
+  /// only non-empty under [`RemainderPolicy::Burn`].
+  ///
+  /// Nested distributions are flattened into the allocation, and the allocation plus the
+  /// remainder is checked to add up to exactly `funds`.
+  pub fn compute_distribution_with_remainder(
+    &self,
+    funds: &CoinSet,
+  ) -> XcosmResult<(Allocation, CoinSet)> {
+    // overrides and minimums make shares differ per denom, so allocate each denom separately
+    let parts = match self.overrides.is_empty() && self.min_payout.is_empty() {
+      true => vec![(None, funds.clone())],
+      false => funds
+        .iter_coins()
+        .map(|coin| Ok((Some(coin.denom.clone()), CoinSet::from_iter_summed([coin])?)))
+        .collect::<XcosmResult<Vec<_>>>()?,
+    };
+    let mut merged: BTreeMap<Addr, CoinSet> = BTreeMap::new();
+    let mut rem = CoinSet::default();
+    for (denom, part) in parts {
+      let (allocation, part_rem) = self.allocate_part(denom.as_deref(), &part)?;
+      for (recipient, coins) in allocation {
+        match recipient {
+          Recipient::Addr(addr) => {
+            merged
+              .entry(addr.clone())
+              .or_default()
+              .try_plus_mut(&coins)?;
+          }
+          Recipient::Nested(i) => {
+            let (nested_allocation, nested_rem) = self.nested[i]
+              .distribution
+              .compute_distribution_with_remainder(&coins)?;
+            for (addr, coins) in nested_allocation {
+              merged.entry(addr).or_default().try_plus_mut(&coins)?;
+            }
+            rem.try_plus_mut(&nested_rem)?;
+          }
+        }
+      }
+      rem.try_plus_mut(&part_rem)?;
+    }
+    let mut total = rem.clone();
+    for coins in merged.values() {
+      total.try_plus_mut(coins)?;
+    }
+    if total.without_zeros() != funds.without_zeros() {
+      return Err(
+        FundError::Unexpected {
+          msg: "distributed funds do not add up to the input funds".to_string(),
+        }
+        .into(),
+      );
+    }
+    Ok((merged.into_iter().collect(), rem))
+  }
+
+  /// Allocate `part` among the claimants, using the overrides and minimum payout for `denom`
+  /// if given. Claimants below the minimum are dropped until every claimant meets it.
+  fn allocate_part(
+    &self,
+    denom: Option<&str>,
+    part: &CoinSet,
+  ) -> XcosmResult<(Vec<(Recipient<'_>, CoinSet)>, CoinSet)> {
+    let mut shares = self.shares(denom);
+    shares.retain(|(_, weight)| !weight.is_zero());
+    loop {
+      let (allocation, rem) = allocate_shares(
+        shares.clone(),
+        Uint128::from(MAX_BPS),
+        part,
+        Rounding::Floor,
+        &self.remainder,
+      )?;
+      let min = match denom.and_then(|denom| self.min_payout.get(denom)) {
+        Some(min) => *min,
+        None => return Ok((allocation, rem)),
+      };
+      let dust: Vec<Recipient> = allocation
+        .iter()
+        .filter(|(recipient, coins)| {
+          matches!(recipient, Recipient::Addr(_))
+            && denom
+              .and_then(|denom| coins.get(denom))
+              .copied()
+              .unwrap_or_default()
+              < min
+        })
+        .map(|(recipient, _)| *recipient)
+        .collect();
+      // a remainder recipient without a claim can hold dust but has no share to drop
+      let count = shares.len();
+      shares.retain(|(recipient, _)| !dust.contains(recipient));
+      if shares.len() == count || shares.is_empty() {
+        return Ok((allocation, rem));
+      }
+    }
+  }
+
+  /// Preview the allocation of `funds` without building any messages.
+  ///
+  /// See [`Distribution::compute_distribution`].
+  pub fn preview(&self, funds: &CoinSet) -> XcosmResult<Allocation> {
+    self.compute_distribution(funds)
+  }
+
+  pub fn distribute_coins(&self, from: &Addr, funds: &CoinSet) -> XcosmResult<Vec<CosmosMsg>> {
+    self
+      .distribute_coins_logged(from, funds)
+      .map(|(msgs, _)| msgs)
+  }
+
+  /// Distribute `funds`, also returning the exact per-recipient allocation that was sent.
+  ///
+  /// A burned remainder is not part of the allocation.
+  pub fn distribute_coins_logged(
+    &self,
+    from: &Addr,
+    funds: &CoinSet,
+  ) -> XcosmResult<(Vec<CosmosMsg>, Allocation)> {
+    let (allocation, rem) = self.compute_distribution_with_remainder(funds)?;
+    Ok((self.target_msgs(from, &allocation, &rem)?, allocation))
+  }
+
+  /// Build the messages paying out an allocation to each claimant's target and burning the
+  /// remainder `rem`.
+  fn target_msgs(
+    &self,
+    from: &Addr,
+    allocation: &Allocation,
+    rem: &CoinSet,
+  ) -> XcosmResult<Vec<CosmosMsg>> {
+    let mut target_msgs: Vec<CosmosMsg> = vec![];
+    let mut bank = Allocation::new();
+    let mut bank_funds = rem.clone();
+    for (addr, coins) in allocation {
+      match self.target_for(addr).cloned().unwrap_or_default() {
+        ClaimTarget::Bank => {
+          bank_funds.try_plus_mut(coins)?;
+          bank.push((addr.clone(), coins.clone()));
+        }
+        #[cfg(feature = "staking")]
+        ClaimTarget::Delegate { validator } => {
+          for amount in coins.without_zeros().iter_coins() {
+            target_msgs.push(
+              StakingMsg::Delegate {
+                validator: validator.clone(),
+                amount,
+              }
+              .into(),
+            );
+          }
+        }
+        #[cfg(not(feature = "staking"))]
+        ClaimTarget::Delegate { .. } => return Err(FundError::StakingDisabled {}.into()),
+      }
+    }
+    let mut msgs = match (bank.is_empty(), rem.is_empty()) {
+      (true, true) => vec![],
+      (true, false) => vec![rem.burn()?],
+      (false, _) => allocation_msgs(from, &bank_funds, &bank, rem)?,
+    };
+    msgs.append(&mut target_msgs);
+    Ok(msgs)
+  }
+
+  /// Distribute `funds`, also returning the exact per-recipient allocation that was sent and
+  /// an event describing it. See [`distribution_event`].
+  pub fn distribute_coins_with_event(
+    &self,
+    from: &Addr,
+    funds: &CoinSet,
+  ) -> XcosmResult<(Vec<CosmosMsg>, Allocation, Event)> {
+    let (allocation, rem) = self.compute_distribution_with_remainder(funds)?;
+    let event = distribution_event(&allocation, &rem);
+    Ok((
+      self.target_msgs(from, &allocation, &rem)?,
+      allocation,
+      event,
+    ))
+  }
+
+  /// Distribute `funds`, also returning a receipt of the distribution as of `block`.
+  pub fn distribute_coins_with_receipt(
+    &self,
+    from: &Addr,
+    funds: &CoinSet,
+    block: &BlockInfo,
+  ) -> XcosmResult<(Vec<CosmosMsg>, DistributionReceipt)> {
+    let (msgs, payouts) = self.distribute_coins_logged(from, funds)?;
+    Ok((
+      msgs,
+      DistributionReceipt::new(block, funds.clone(), payouts),
+    ))
+  }
+
+  /// Distribute `amount` of a cw20 `token` with the same claims and remainder policy as
+  /// [`Distribution::distribute_coins`]. Overrides for the token use its address as the
+  /// denom.
+  pub fn distribute_cw20(&self, token: &Addr, amount: Uint128) -> XcosmResult<Vec<CosmosMsg>> {
+    self
+      .distribute_cw20_logged(token, amount)
+      .map(|(msgs, _)| msgs)
+  }
+
+  /// Distribute a cw20 token, also returning the exact per-recipient amounts that were sent.
+  ///
+  /// A burned remainder is not part of the allocation.
+  pub fn distribute_cw20_logged(
+    &self,
+    token: &Addr,
+    amount: Uint128,
+  ) -> XcosmResult<(Vec<CosmosMsg>, TokenAllocation)> {
+    let denom = token.to_string();
+    let mut funds = CoinSet::default();
+    funds.insert(denom.clone(), amount);
+    let (allocation, rem) = self.compute_distribution_with_remainder(&funds)?;
+    let allocation: TokenAllocation = allocation
+      .into_iter()
+      .map(|(addr, coins)| (addr, coins.get(&denom).copied().unwrap_or_default()))
+      .filter(|(_, amount)| !amount.is_zero())
+      .collect();
+    let mut msgs = allocation
       .iter()
-      .map(|(addr, claim)| {
-        let claimed = claim.claim(funds)?;
-        rem.try_minus_mut(&claimed)?;
-        Ok((addr, claim.claim(funds)?))
-      })
-      .collect::<XcosmResult<Vec<(&Addr, CoinSet)>>>()?;
-    // give remainder to first claim
-    // TODO make this behavior configurable
-    claimed
-      .first_mut()
-      .map(|(_, coins)| coins.try_plus_mut(&rem))
-      .transpose()?
-      .ok_or_else(|| FundError::Unexpected {
-        msg: "distribution claims are not empty but no claimed funds were calculated".to_string(),
-      })?;
-    funds.send_many(from, claimed).into_result()
+      .map(|(addr, amount)| send_cw20(token, *amount, addr))
+      .collect::<XcosmResult<Vec<CosmosMsg>>>()?;
+    if let Some(burned) = rem.get(&denom) {
+      msgs.push(burn_cw20(token, *burned)?);
+    }
+    Ok((msgs, allocation))
+  }
+}
+
+/// Allocate `funds` to each address by `weight / denominator`, sorted by address, with the
+/// remainder handled by `policy`. Returns the allocation and any burned remainder.
+///
+/// See [`allocate_shares`].
+fn allocate_addr_shares(
+  shares: Vec<(&Addr, Uint128)>,
+  denominator: Uint128,
+  funds: &CoinSet,
+  rounding: Rounding,
+  policy: &RemainderPolicy,
+) -> XcosmResult<(Allocation, CoinSet)> {
+  let (allocation, rem) = allocate_shares(shares, denominator, funds, rounding, policy)?;
+  Ok((
+    allocation
+      .into_iter()
+      .map(|(addr, coins)| (addr.clone(), coins))
+      .collect(),
+    rem,
+  ))
+}
+
+/// Allocate `funds` to each recipient by `weight / denominator`, sorted by recipient, with
+/// the remainder handled by `policy`. Returns the allocation and any burned remainder.
+///
+/// Zero weights are skipped. Allocated and remaining funds always sum to `funds`, and
+/// rounding that would allocate more than `funds` fails.
+fn allocate_shares<'a, K: Ord+Clone+From<&'a Addr>>(
+  mut shares: Vec<(K, Uint128)>,
+  denominator: Uint128,
+  funds: &CoinSet,
+  rounding: Rounding,
+  policy: &'a RemainderPolicy,
+) -> XcosmResult<(Vec<(K, CoinSet)>, CoinSet)> {
+  shares.retain(|(_, weight)| !weight.is_zero());
+  if shares.is_empty() {
+    return Err(FundError::DistributionUnclaimed {}.into());
+  }
+  shares.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+  if *policy == RemainderPolicy::Redistribute {
+    let weights: Vec<Uint128> = shares.iter().map(|(_, weight)| *weight).collect();
+    let allocation = shares
+      .into_iter()
+      .map(|(recipient, _)| recipient)
+      .zip(funds.split(&weights)?)
+      .collect();
+    return Ok((allocation, CoinSet::default()));
+  }
+  let mut rem = funds.clone();
+  let mut claimed = shares
+    .iter()
+    .map(|(recipient, weight)| {
+      let claimed = funds.try_mul_ratio(*weight, denominator, rounding)?;
+      rem
+        .try_minus_mut(&claimed)
+        .map_err(|_| FundError::DistributionOverclaimed {})?;
+      Ok((recipient.clone(), claimed))
+    })
+    .collect::<XcosmResult<Vec<(K, CoinSet)>>>()?;
+  let rem = rem.without_zeros();
+  if rem.is_empty() {
+    return Ok((claimed, rem));
+  }
+  let recipient = match policy {
+    RemainderPolicy::FirstClaim | RemainderPolicy::Redistribute => 0,
+    RemainderPolicy::LargestClaim => {
+      shares
+        .iter()
+        .enumerate()
+        .fold(
+          (0, Uint128::zero()),
+          |(largest, max), (i, (_, weight))| match *weight > max {
+            true => (i, *weight),
+            false => (largest, max),
+          },
+        )
+        .0
+    }
+    RemainderPolicy::SpecificAddress(addr) => {
+      let addr = K::from(addr);
+      match claimed.binary_search_by(|(recipient, _)| recipient.cmp(&addr)) {
+        Ok(i) => i,
+        Err(i) => {
+          claimed.insert(i, (addr, CoinSet::default()));
+          i
+        }
+      }
+    }
+    RemainderPolicy::Burn => return Ok((claimed, rem)),
+  };
+  claimed
+    .get_mut(recipient)
+    .map(|(_, coins)| coins.try_plus_mut(&rem))
+    .transpose()?
+    .ok_or_else(|| FundError::Unexpected {
+      msg: "distribution claims are not empty but no claimed funds were calculated".to_string(),
+    })?;
+  Ok((claimed, CoinSet::default()))
+}
+
+/// Record of a completed distribution, e.g. for storing historical payouts.
+#[cw_serde]
+pub struct DistributionReceipt {
+  pub height: u64,
+  pub time: Timestamp,
+  /// Funds distributed, including any burned remainder.
+  pub total: CoinSet,
+  /// Funds sent to each recipient, sorted by address.
+  pub payouts: Allocation,
+}
+
+impl DistributionReceipt {
+  pub fn new(block: &BlockInfo, total: CoinSet, payouts: Allocation) -> Self {
+    Self {
+      height: block.height,
+      time: block.time,
+      total,
+      payouts,
+    }
+  }
+
+  /// Get the funds that were burned rather than paid out.
+  pub fn burned(&self) -> XcosmResult<CoinSet> {
+    let mut burned = self.total.clone();
+    for (_, coins) in self.payouts.iter() {
+      burned.try_minus_mut(coins)?;
+    }
+    Ok(burned.without_zeros())
+  }
+
+  /// Get the funds paid to `addr`.
+  pub fn payout_for(&self, addr: &Addr) -> Option<&CoinSet> {
+    self
+      .payouts
+      .binary_search_by(|(payee, _)| payee.cmp(addr))
+      .ok()
+      .map(|i| &self.payouts[i].1)
+  }
+}
+
+/// Build an event of type [`DISTRIBUTION_EVENT_TYPE`] with an attribute per recipient of
+/// `allocation`, keyed by address, and a `burned` attribute if `burned` is non-empty.
+/// Amounts are formatted as comma-separated coins, e.g. `100uatom,5uosmo`.
+pub fn distribution_event(allocation: &Allocation, burned: &CoinSet) -> Event {
+  let format_coins = |coins: &CoinSet| {
+    coins
+      .without_zeros()
+      .iter_coins()
+      .map(|coin| coin.to_string())
+      .collect::<Vec<_>>()
+      .join(",")
+  };
+  let event = Event::new(DISTRIBUTION_EVENT_TYPE).add_attributes(
+    allocation
+      .iter()
+      .map(|(addr, coins)| (addr.to_string(), format_coins(coins))),
+  );
+  match burned.without_zeros().is_empty() {
+    true => event,
+    false => event.add_attribute("burned", format_coins(burned)),
+  }
+}
+
+/// Build the messages sending an allocation of `funds` and burning the remainder `rem`.
+fn allocation_msgs(
+  from: &Addr,
+  funds: &CoinSet,
+  allocation: &Allocation,
+  rem: &CoinSet,
+) -> XcosmResult<Vec<CosmosMsg>> {
+  let sent = funds.try_minus(rem)?;
+  let mut msgs = sent.send_many(
+    from,
+    allocation
+      .iter()
+      .map(|(addr, coins)| (addr, coins.clone()))
+      .collect(),
+  )?;
+  if !rem.is_empty() {
+    msgs.push(rem.burn()?);
+  }
+  Ok(msgs)
+}
+
+impl Deref for Distribution {
+  type Target = BTreeMap<Addr, Claim>;
+
+  fn deref(&self) -> &Self::Target {
+    &self.claims
+  }
+}
+
+impl DerefMut for Distribution {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.claims
   }
 }
 
 impl Default for Distribution {
   fn default() -> Self {
-    Self::new(HashMap::new())
+    Self::new(BTreeMap::new())
   }
 }
 
-impl From<HashMap<Addr, Claim>> for Distribution {
-  fn from(claims: HashMap<Addr, Claim>) -> Self {
-    Self(claims)
+impl From<BTreeMap<Addr, Claim>> for Distribution {
+  fn from(claims: BTreeMap<Addr, Claim>) -> Self {
+    Self::new(claims)
   }
 }
 
-impl Into<DistributionMsg> for Distribution {
-  fn into(self) -> DistributionMsg {
-    DistributionMsg(
-      self
+impl From<Distribution> for DistributionMsg {
+  fn from(distribution: Distribution) -> Self {
+    DistributionMsg {
+      claims: distribution
         .iter()
         .map(|(addr, claim)| (addr.to_string(), *claim))
         .collect(),
-    )
+      overrides: distribution
+        .overrides
+        .into_iter()
+        .map(|(addr, denoms)| (addr.to_string(), denoms))
+        .collect(),
+      remainder: distribution.remainder,
+      nested: distribution
+        .nested
+        .into_iter()
+        .map(|nested| NestedDistributionMsg {
+          claim: nested.claim,
+          distribution: nested.distribution.into(),
+        })
+        .collect(),
+      targets: distribution
+        .targets
+        .into_iter()
+        .map(|(addr, target)| (addr.to_string(), target))
+        .collect(),
+      min_payout: distribution.min_payout,
+    }
   }
 }
 
+/// Message form of a [`Distribution`], also accepting the earlier bare map of claims.
+#[cw_serde]
+#[serde(from = "DistributionJson<String, NestedDistributionMsg>")]
+pub struct DistributionMsg {
+  pub claims: BTreeMap<String, Claim>,
+  #[serde(default)]
+  pub overrides: BTreeMap<String, BTreeMap<String, Bps>>,
+  #[serde(default)]
+  pub remainder: RemainderPolicy,
+  #[serde(default)]
+  pub nested: Vec<NestedDistributionMsg>,
+  #[serde(default)]
+  pub targets: BTreeMap<String, ClaimTarget>,
+  #[serde(default)]
+  pub min_payout: CoinSet,
+}
+
 #[cw_serde]
-#[derive(Deref, DerefMut)]
-pub struct DistributionMsg(HashMap<String, Claim>);
+pub struct NestedDistributionMsg {
+  pub claim: Claim,
+  pub distribution: DistributionMsg,
+}
+
+/// Either JSON format of a [`Distribution`] or [`DistributionMsg`].
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum DistributionJson<K: Ord, N> {
+  Current(DistributionFields<K, N>),
+  /// Bare map of claims, from before distributions had options.
+  Legacy(BTreeMap<K, Claim>),
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DistributionFields<K: Ord, N> {
+  claims: BTreeMap<K, Claim>,
+  #[serde(default = "BTreeMap::new")]
+  overrides: BTreeMap<K, BTreeMap<String, Bps>>,
+  #[serde(default)]
+  remainder: RemainderPolicy,
+  #[serde(default = "Vec::new")]
+  nested: Vec<N>,
+  #[serde(default = "BTreeMap::new")]
+  targets: BTreeMap<K, ClaimTarget>,
+  #[serde(default)]
+  min_payout: CoinSet,
+}
+
+impl From<DistributionJson<Addr, NestedDistribution>> for Distribution {
+  fn from(json: DistributionJson<Addr, NestedDistribution>) -> Self {
+    match json {
+      DistributionJson::Current(DistributionFields {
+        claims,
+        overrides,
+        remainder,
+        nested,
+        targets,
+        min_payout,
+      }) => Self {
+        claims,
+        overrides,
+        remainder,
+        nested,
+        targets,
+        min_payout,
+      },
+      DistributionJson::Legacy(claims) => Self::new(claims),
+    }
+  }
+}
+
+impl From<DistributionJson<String, NestedDistributionMsg>> for DistributionMsg {
+  fn from(json: DistributionJson<String, NestedDistributionMsg>) -> Self {
+    match json {
+      DistributionJson::Current(DistributionFields {
+        claims,
+        overrides,
+        remainder,
+        nested,
+        targets,
+        min_payout,
+      }) => Self {
+        claims,
+        overrides,
+        remainder,
+        nested,
+        targets,
+        min_payout,
+      },
+      DistributionJson::Legacy(claims) => Self {
+        claims,
+        overrides: BTreeMap::new(),
+        remainder: RemainderPolicy::default(),
+        nested: vec![],
+        targets: BTreeMap::new(),
+        min_payout: CoinSet::default(),
+      },
+    }
+  }
+}
+
+impl Deref for DistributionMsg {
+  type Target = BTreeMap<String, Claim>;
+
+  fn deref(&self) -> &Self::Target {
+    &self.claims
+  }
+}
+
+impl DerefMut for DistributionMsg {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.claims
+  }
+}
 
 impl<'a> ApiValidator<'a, Distribution> for &DistributionMsg {
   fn api_validate(self, api: &dyn Api) -> XcosmResult<Distribution> {
-    self
+    let claims = self
       .iter()
       .map(|(addr_str, claim)| Ok::<_, XcosmError>((api.validate(&addr_str)?, *claim)))
-      .collect::<XcosmResult<HashMap<Addr, Claim>>>()
-      .map(Into::into)
+      .collect::<XcosmResult<BTreeMap<Addr, Claim>>>()?;
+    let mut distribution = Distribution::new(claims)
+      .with_remainder_policy(api.validate(&self.remainder)?)
+      .with_min_payout(self.min_payout.clone());
+    for (addr_str, denoms) in self.overrides.iter() {
+      distribution
+        .overrides
+        .insert(api.validate(&addr_str)?, denoms.clone());
+    }
+    for nested in self.nested.iter() {
+      distribution = distribution.with_nested(nested.claim, api.validate(&nested.distribution)?);
+    }
+    for (addr_str, target) in self.targets.iter() {
+      distribution = distribution.with_target(api.validate(&addr_str)?, target.clone());
+    }
+    Ok(distribution)
   }
 }
 
-pub trait MessageFunds {
-  fn expect_funds(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult;
-  fn expect_funds_exact(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult;
-  fn expect_no_funds(&self) -> XcosmResult;
-  fn fund_set(&self) -> XcosmResult<CoinSet>;
+impl<'a> ApiValidator<'a, RemainderPolicy> for &RemainderPolicy {
+  /// Validate the address of [`RemainderPolicy::SpecificAddress`].
+  fn api_validate(self, api: &dyn Api) -> XcosmResult<RemainderPolicy> {
+    match self {
+      RemainderPolicy::SpecificAddress(addr) => Ok(RemainderPolicy::SpecificAddress(
+        api.validate(&addr.as_str())?,
+      )),
+      remainder => Ok(remainder.clone()),
+    }
+  }
 }
 
-impl MessageFunds for MessageInfo {
-  fn expect_funds<'a>(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult {
-    self.fund_set()?.expect_coins_exact(expected)
+/// Distribution by arbitrary weights, e.g. token counts, normalized against the total weight
+/// when distributing.
+#[cw_serde]
+pub struct WeightedDistribution {
+  pub weights: BTreeMap<Addr, Uint128>,
+  #[serde(default)]
+  pub remainder: RemainderPolicy,
+}
+
+impl WeightedDistribution {
+  pub fn new(weights: BTreeMap<Addr, Uint128>) -> Self {
+    Self {
+      weights,
+      remainder: RemainderPolicy::default(),
+    }
   }
 
-  fn expect_funds_exact<'a>(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult {
-    self.fund_set()?.expect_coins_exact(expected)
+  /// Set the policy for funds left over after rounding.
+  pub fn with_remainder_policy(mut self, remainder: RemainderPolicy) -> Self {
+    self.remainder = remainder;
+    self
   }
 
-  fn expect_no_funds(&self) -> XcosmResult {
-    self.fund_set()?.expect_none()
+  pub fn total_weight(&self) -> MathResult<Uint128> {
+    self
+      .weights
+      .values()
+      .try_fold(Uint128::zero(), |total, weight| total.checked_add(*weight))
+      .map_err(Into::into)
   }
 
-  fn fund_set(&self) -> XcosmResult<CoinSet> {
+  /// Compute the exact per-recipient allocation of `funds`, sorted by address.
+  ///
+  /// Zero weights are skipped and any remainder is handled by the [`RemainderPolicy`]. Burned
+  /// remainders are left out, see
+  /// [`WeightedDistribution::compute_distribution_with_remainder`].
+  pub fn compute_distribution(&self, funds: &CoinSet) -> XcosmResult<Allocation> {
+    self
+      .compute_distribution_with_remainder(funds)
+      .map(|(allocation, _)| allocation)
+  }
+
+  /// Compute the allocation of `funds` along with the remainder left unallocated, which is
+  /// only non-empty under [`RemainderPolicy::Burn`].
+  pub fn compute_distribution_with_remainder(
+    &self,
+    funds: &CoinSet,
+  ) -> XcosmResult<(Allocation, CoinSet)> {
+    allocate_addr_shares(
+      self
+        .weights
+        .iter()
+        .map(|(addr, weight)| (addr, *weight))
+        .collect(),
+      self.total_weight()?,
+      funds,
+      Rounding::Floor,
+      &self.remainder,
+    )
+  }
+
+  /// Preview the allocation of `funds` without building any messages.
+  ///
+  /// See [`WeightedDistribution::compute_distribution`].
+  pub fn preview(&self, funds: &CoinSet) -> XcosmResult<Allocation> {
+    self.compute_distribution(funds)
+  }
+
+  pub fn distribute_coins(&self, from: &Addr, funds: &CoinSet) -> XcosmResult<Vec<CosmosMsg>> {
+    self
+      .distribute_coins_logged(from, funds)
+      .map(|(msgs, _)| msgs)
+  }
+
+  /// Distribute `funds`, also returning the exact per-recipient allocation that was sent.
+  ///
+  /// A burned remainder is not part of the allocation.
+  pub fn distribute_coins_logged(
+    &self,
+    from: &Addr,
+    funds: &CoinSet,
+  ) -> XcosmResult<(Vec<CosmosMsg>, Allocation)> {
+    let (allocation, rem) = self.compute_distribution_with_remainder(funds)?;
+    Ok((allocation_msgs(from, funds, &allocation, &rem)?, allocation))
+  }
+}
+
+#[cw_serde]
+pub struct WeightedDistributionMsg {
+  pub weights: BTreeMap<String, Uint128>,
+  #[serde(default)]
+  pub remainder: RemainderPolicy,
+}
+
+impl<'a> ApiValidator<'a, WeightedDistribution> for &WeightedDistributionMsg {
+  fn api_validate(self, api: &dyn Api) -> XcosmResult<WeightedDistribution> {
+    let weights = self
+      .weights
+      .iter()
+      .map(|(addr_str, weight)| Ok::<_, XcosmError>((api.validate(&addr_str)?, *weight)))
+      .collect::<XcosmResult<BTreeMap<Addr, Uint128>>>()?;
+    Ok(WeightedDistribution::new(weights).with_remainder_policy(api.validate(&self.remainder)?))
+  }
+}
+
+/// Distribution by [`Decimal`] fractions of the funds, summing to at most one.
+#[cw_serde]
+pub struct DecimalDistribution {
+  pub shares: BTreeMap<Addr, Decimal>,
+  #[serde(default)]
+  pub rounding: Rounding,
+  #[serde(default)]
+  pub remainder: RemainderPolicy,
+}
+
+impl DecimalDistribution {
+  pub fn new(shares: BTreeMap<Addr, Decimal>) -> Self {
+    Self {
+      shares,
+      rounding: Rounding::default(),
+      remainder: RemainderPolicy::default(),
+    }
+  }
+
+  /// Set the rounding of each share. Rounding up fails if it would allocate more than the
+  /// funds.
+  pub fn with_rounding(mut self, rounding: Rounding) -> Self {
+    self.rounding = rounding;
+    self
+  }
+
+  /// Set the policy for funds left over after rounding.
+  pub fn with_remainder_policy(mut self, remainder: RemainderPolicy) -> Self {
+    self.remainder = remainder;
+    self
+  }
+
+  pub fn total_shares(&self) -> XcosmResult<Decimal> {
+    let total = self
+      .shares
+      .values()
+      .try_fold(Decimal::zero(), |total, share| total.checked_add(*share))
+      .map_err(MathError::from)?;
+    if total > Decimal::one() {
+      return Err(FundError::DistributionOverclaimed {}.into());
+    }
+    Ok(total)
+  }
+
+  /// Compute the exact per-recipient allocation of `funds`, sorted by address.
+  ///
+  /// Zero shares are skipped and any remainder is handled by the [`RemainderPolicy`]. Burned
+  /// remainders are left out, see
+  /// [`DecimalDistribution::compute_distribution_with_remainder`].
+  pub fn compute_distribution(&self, funds: &CoinSet) -> XcosmResult<Allocation> {
+    self
+      .compute_distribution_with_remainder(funds)
+      .map(|(allocation, _)| allocation)
+  }
+
+  /// Compute the allocation of `funds` along with the remainder left unallocated, which is
+  /// only non-empty under [`RemainderPolicy::Burn`].
+  pub fn compute_distribution_with_remainder(
+    &self,
+    funds: &CoinSet,
+  ) -> XcosmResult<(Allocation, CoinSet)> {
+    self.total_shares()?;
+    allocate_addr_shares(
+      self
+        .shares
+        .iter()
+        .map(|(addr, share)| (addr, share.atomics()))
+        .collect(),
+      Decimal::one().atomics(),
+      funds,
+      self.rounding,
+      &self.remainder,
+    )
+  }
+
+  /// Preview the allocation of `funds` without building any messages.
+  ///
+  /// See [`DecimalDistribution::compute_distribution`].
+  pub fn preview(&self, funds: &CoinSet) -> XcosmResult<Allocation> {
+    self.compute_distribution(funds)
+  }
+
+  pub fn distribute_coins(&self, from: &Addr, funds: &CoinSet) -> XcosmResult<Vec<CosmosMsg>> {
+    self
+      .distribute_coins_logged(from, funds)
+      .map(|(msgs, _)| msgs)
+  }
+
+  /// Distribute `funds`, also returning the exact per-recipient allocation that was sent.
+  ///
+  /// A burned remainder is not part of the allocation.
+  pub fn distribute_coins_logged(
+    &self,
+    from: &Addr,
+    funds: &CoinSet,
+  ) -> XcosmResult<(Vec<CosmosMsg>, Allocation)> {
+    let (allocation, rem) = self.compute_distribution_with_remainder(funds)?;
+    Ok((allocation_msgs(from, funds, &allocation, &rem)?, allocation))
+  }
+}
+
+#[cw_serde]
+pub struct DecimalDistributionMsg {
+  pub shares: BTreeMap<String, Decimal>,
+  #[serde(default)]
+  pub rounding: Rounding,
+  #[serde(default)]
+  pub remainder: RemainderPolicy,
+}
+
+impl<'a> ApiValidator<'a, DecimalDistribution> for &DecimalDistributionMsg {
+  fn api_validate(self, api: &dyn Api) -> XcosmResult<DecimalDistribution> {
+    let shares = self
+      .shares
+      .iter()
+      .map(|(addr_str, share)| Ok::<_, XcosmError>((api.validate(&addr_str)?, *share)))
+      .collect::<XcosmResult<BTreeMap<Addr, Decimal>>>()?;
+    Ok(
+      DecimalDistribution::new(shares)
+        .with_rounding(self.rounding)
+        .with_remainder_policy(api.validate(&self.remainder)?),
+    )
+  }
+}
+
+pub trait MessageFunds {
+  /// Require funds to contain at least the expected amounts.
+  fn expect_funds(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult;
+  /// Require funds to contain at least the expected amounts and get the surplus. Zero
+  /// amounts are left out of the surplus.
+  fn expect_funds_at_least(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult<CoinSet>;
+  /// Require funds to contain only the expected denoms at exactly the expected amounts.
+  fn expect_funds_exact(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult;
+  fn expect_no_funds(&self) -> XcosmResult;
+  /// Require funds to contain only denoms permitted by `filter`. See
+  /// [`CoinSet::expect_only_denoms`].
+  fn expect_fund_denoms(&self, filter: &DenomFilter) -> XcosmResult;
+  /// Get funds passed through a denom filter. See [`DenomFilter::apply`].
+  fn filter_funds(&self, filter: &DenomFilter) -> XcosmResult<CoinSet>;
+  fn fund_set(&self) -> XcosmResult<CoinSet>;
+  /// Create a bank send returning all funds to the sender, or `None` if no funds were sent.
+  fn refund_all(&self) -> XcosmResult<Option<CosmosMsg>>;
+  /// Require funds to contain at least the expected amounts and create a bank send returning
+  /// the surplus to the sender, or `None` if there is no surplus.
+  fn refund_surplus(
+    &self,
+    expected: impl IntoIterator<Item=Coin>,
+  ) -> XcosmResult<Option<CosmosMsg>>;
+}
+
+impl MessageFunds for MessageInfo {
+  fn expect_funds(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult {
+    self.fund_set()?.expect_coins(expected)
+  }
+
+  fn expect_funds_at_least(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult<CoinSet> {
+    self.fund_set()?.expect_coins_with_change(expected)
+  }
+
+  fn expect_funds_exact(&self, expected: impl IntoIterator<Item=Coin>) -> XcosmResult {
+    self.fund_set()?.expect_coins_exact(expected)
+  }
+
+  fn expect_no_funds(&self) -> XcosmResult {
+    self.fund_set()?.expect_none()
+  }
+
+  fn expect_fund_denoms(&self, filter: &DenomFilter) -> XcosmResult {
+    self.fund_set()?.expect_only_denoms(filter)
+  }
+
+  fn filter_funds(&self, filter: &DenomFilter) -> XcosmResult<CoinSet> {
+    filter.apply(self.fund_set()?)
+  }
+
+  fn fund_set(&self) -> XcosmResult<CoinSet> {
     self.funds.clone().try_into()
   }
+
+  fn refund_all(&self) -> XcosmResult<Option<CosmosMsg>> {
+    refund(self.fund_set()?, &self.sender)
+  }
+
+  fn refund_surplus(
+    &self,
+    expected: impl IntoIterator<Item=Coin>,
+  ) -> XcosmResult<Option<CosmosMsg>> {
+    refund(self.expect_funds_at_least(expected)?, &self.sender)
+  }
+}
+
+/// Funds held by the contract itself.
+pub trait EnvFunds {
+  /// Query every coin held by the contract.
+  fn contract_balance<C: CustomQuery>(&self, querier: &QuerierWrapper<C>) -> XcosmResult<CoinSet>;
+  /// Query the coins held by the contract that are left after setting aside `reserved`, e.g.
+  /// funds owed to users. Zero amounts are left out.
+  ///
+  /// Requires the contract to hold at least `reserved`.
+  fn available_after<C: CustomQuery>(
+    &self,
+    querier: &QuerierWrapper<C>,
+    reserved: &CoinSet,
+  ) -> XcosmResult<CoinSet>;
+}
+
+impl EnvFunds for Env {
+  fn contract_balance<C: CustomQuery>(&self, querier: &QuerierWrapper<C>) -> XcosmResult<CoinSet> {
+    querier
+      .query_all_balances(&self.contract.address)?
+      .try_into()
+  }
+
+  fn available_after<C: CustomQuery>(
+    &self,
+    querier: &QuerierWrapper<C>,
+    reserved: &CoinSet,
+  ) -> XcosmResult<CoinSet> {
+    self
+      .contract_balance(querier)?
+      .expect_coins_with_change(reserved.iter_coins())
+  }
+}
+
+/// Send non-zero `coins` back to `to`, or `None` if there are none.
+fn refund(coins: CoinSet, to: &Addr) -> XcosmResult<Option<CosmosMsg>> {
+  let coins = coins.without_zeros();
+  match coins.is_empty() {
+    true => Ok(None),
+    false => coins.send(to).map(Some),
+  }
+}
+
+/// Protocol fee taken from funds before distributing the rest.
+#[cw_serde]
+pub struct FeeSplit {
+  pub fee: Claim,
+  pub fee_recipient: Addr,
+  pub distribution: Distribution,
+}
+
+impl FeeSplit {
+  pub fn new(fee: Claim, fee_recipient: Addr, distribution: Distribution) -> Self {
+    Self {
+      fee,
+      fee_recipient,
+      distribution,
+    }
+  }
+
+  /// Split `funds` into the fee, rounded down, and the rest left to distribute.
+  pub fn split(&self, funds: &CoinSet) -> XcosmResult<(CoinSet, CoinSet)> {
+    let fee = self.fee.claim(funds)?;
+    let rest = funds.try_minus(&fee)?;
+    Ok((fee.without_zeros(), rest))
+  }
+
+  /// Create the messages sending the fee and distributing the rest of `funds` from `from`.
+  ///
+  /// No fee message is created if the fee rounds down to nothing.
+  pub fn apply(
+    &self,
+    from: &Addr,
+    funds: &CoinSet,
+  ) -> XcosmResult<(Vec<CosmosMsg>, Vec<CosmosMsg>)> {
+    let (fee, rest) = self.split(funds)?;
+    let fee_msgs = match fee.is_empty() {
+      true => vec![],
+      false => vec![fee.send(&self.fee_recipient)?],
+    };
+    Ok((fee_msgs, self.distribution.distribute_coins(from, &rest)?))
+  }
+}
+
+/// Fee rate charged on the part of an amount above `threshold`, up to the next tier.
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub struct FeeTier {
+  pub threshold: Uint128,
+  pub bps: Bps,
+}
+
+/// Progressive fee charging each tier's rate on the part of an amount within that tier, like
+/// marginal tax brackets. Amounts below the first threshold are free.
+#[cw_serde]
+pub struct FeeSchedule {
+  pub tiers: Vec<FeeTier>,
+  #[serde(default)]
+  pub rounding: Rounding,
+}
+
+impl FeeSchedule {
+  /// Requires strictly increasing thresholds and bps of at most 100%.
+  pub fn new(tiers: Vec<FeeTier>) -> XcosmResult<Self> {
+    let schedule = Self {
+      tiers,
+      rounding: Rounding::default(),
+    };
+    schedule.validate()?;
+    Ok(schedule)
+  }
+
+  /// Set the rounding of the total fee.
+  pub fn with_rounding(mut self, rounding: Rounding) -> Self {
+    self.rounding = rounding;
+    self
+  }
+
+  /// Require strictly increasing thresholds and bps of at most 100%.
+  pub fn validate(&self) -> XcosmResult {
+    for tier in self.tiers.iter() {
+      tier.bps.expect_bounded()?;
+    }
+    match self
+      .tiers
+      .windows(2)
+      .find(|pair| pair[0].threshold >= pair[1].threshold)
+    {
+      Some(pair) => Err(
+        FundError::FeeTiersUnordered {
+          prev: pair[0].threshold,
+          threshold: pair[1].threshold,
+        }
+        .into(),
+      ),
+      None => Ok(()),
+    }
+  }
+
+  /// Get the fee for `amount`. The marginal fees of all tiers are summed before rounding
+  /// once, so the fee never exceeds `amount`.
+  pub fn fee_for(&self, amount: Uint128) -> XcosmResult<Uint128> {
+    self.validate()?;
+    let mut weighted = Uint256::zero();
+    for (i, tier) in self.tiers.iter().enumerate() {
+      if amount <= tier.threshold {
+        break;
+      }
+      let upper = self
+        .tiers
+        .get(i + 1)
+        .map_or(amount, |next| next.threshold.min(amount));
+      weighted = weighted
+        .checked_add((upper - tier.threshold).full_mul(Uint128::from(tier.bps.u32())))
+        .map_err(MathError::from)?;
+    }
+    let fee = self
+      .rounding
+      .mul_ratio256(weighted, Uint256::one(), Uint256::from(MAX_BPS))?;
+    Ok(Uint128::try_from(fee).map_err(|_| MathError::from(ContainerError::Overflow {}))?)
+  }
+
+  /// Get the fee for each denom of `funds`, with the tiers applied to each amount separately.
+  /// Zero fees are left out.
+  pub fn fee_for_coins(&self, funds: &CoinSet) -> XcosmResult<CoinSet> {
+    let mut fees = CoinSet::default();
+    for (denom, amount) in funds.iter() {
+      fees.insert(denom.clone(), self.fee_for(*amount)?);
+    }
+    Ok(fees.without_zeros())
+  }
+}
+
+/// Condition under which escrowed funds may be released to the recipient.
+#[cw_serde]
+pub enum ReleaseCondition {
+  /// Released by an authorized address at any time.
+  Approval(Authorized),
+  /// Released by anyone once expired.
+  Timeout(Expiration),
+  /// Released by an authorized address once expired.
+  ApprovalAfter {
+    approvers: Authorized,
+    expiration: Expiration,
+  },
+}
+
+impl ReleaseCondition {
+  /// Require `requestor` to be able to release as of `block`.
+  pub fn expect_releasable(&self, requestor: &Addr, block: &BlockInfo) -> XcosmResult {
+    let expect_expired = |expiration: &Expiration| match expiration.is_expired(block) {
+      true => Ok(()),
+      false => Err(FundError::EscrowLocked {
+        expiration: *expiration,
+      }),
+    };
+    match self {
+      ReleaseCondition::Approval(approvers) => approvers.authorize(requestor),
+      ReleaseCondition::Timeout(expiration) => Ok(expect_expired(expiration)?),
+      ReleaseCondition::ApprovalAfter {
+        approvers,
+        expiration,
+      } => {
+        approvers.authorize(requestor)?;
+        Ok(expect_expired(expiration)?)
+      }
+    }
+  }
+
+  /// Get the approvers, if release requires approval.
+  pub fn approvers(&self) -> Option<&Authorized> {
+    match self {
+      ReleaseCondition::Approval(approvers) => Some(approvers),
+      ReleaseCondition::Timeout(_) => None,
+      ReleaseCondition::ApprovalAfter { approvers, .. } => Some(approvers),
+    }
+  }
+}
+
+/// Funds held for `recipient` until the release condition is met, refundable to `depositor`.
+#[cw_serde]
+pub struct Escrow {
+  pub depositor: Addr,
+  pub recipient: Addr,
+  pub funds: CoinSet,
+  pub release: ReleaseCondition,
+  /// Once expired, the escrow can no longer be released and anyone may refund it.
+  #[serde(default)]
+  pub refund_after: Expiration,
+}
+
+impl Escrow {
+  pub fn new(depositor: Addr, recipient: Addr, funds: CoinSet, release: ReleaseCondition) -> Self {
+    Self {
+      depositor,
+      recipient,
+      funds,
+      release,
+      refund_after: Expiration::Never {},
+    }
+  }
+
+  /// Allow refunds once `expiration` passes.
+  pub fn with_refund_after(mut self, expiration: Expiration) -> Self {
+    self.refund_after = expiration;
+    self
+  }
+
+  /// Create the message releasing the funds to the recipient.
+  ///
+  /// Requires `requestor` to meet the release condition before the refund expiration.
+  pub fn release(&self, requestor: &Addr, block: &BlockInfo) -> XcosmResult<CosmosMsg> {
+    if self.refund_after.is_expired(block) {
+      return Err(
+        FundError::EscrowExpired {
+          expiration: self.refund_after,
+        }
+        .into(),
+      );
+    }
+    self.release.expect_releasable(requestor, block)?;
+    self.funds.send(&self.recipient)
+  }
+
+  /// Create the message refunding the funds to the depositor.
+  ///
+  /// Requires the refund expiration to have passed or `requestor` to be an approver.
+  pub fn refund(&self, requestor: &Addr, block: &BlockInfo) -> XcosmResult<CosmosMsg> {
+    let approved = self
+      .release
+      .approvers()
+      .is_some_and(|approvers| approvers.authorize(requestor).is_ok());
+    if !approved && !self.refund_after.is_expired(block) {
+      return Err(FundError::EscrowNotRefundable {}.into());
+    }
+    self.funds.send(&self.depositor)
+  }
+}
+
+/// Continuous payment stream accruing `rate_per_second` to `recipient` from `start`, until
+/// `end` if set. Rates may be fractional, with whole coins accruing as they add up.
+#[cw_serde]
+#[derive(Eq)]
+pub struct PaymentStream {
+  pub recipient: Addr,
+  pub rate_per_second: DecCoinSet,
+  pub start: Timestamp,
+  /// End of accrual, or `None` to accrue indefinitely.
+  #[serde(default)]
+  pub end: Option<Timestamp>,
+  /// Coins withdrawn with [`PaymentStream::withdraw`].
+  #[serde(default)]
+  pub withdrawn: CoinSet,
+}
+
+impl PaymentStream {
+  pub fn new(recipient: Addr, rate_per_second: impl Into<DecCoinSet>, start: Timestamp) -> Self {
+    Self {
+      recipient,
+      rate_per_second: rate_per_second.into(),
+      start,
+      end: None,
+      withdrawn: CoinSet::default(),
+    }
+  }
+
+  /// Stop accruing at `end`.
+  pub fn with_end(mut self, end: Timestamp) -> Self {
+    self.end = Some(end);
+    self
+  }
+
+  /// Total whole coins accrued by `now`, rounded down and ignoring any prior claims.
+  ///
+  /// Accrues nothing before `start` and nothing more after `end`.
+  pub fn accrued(&self, now: Timestamp) -> MathResult<CoinSet> {
+    let elapsed = self
+      .end
+      .map_or(now, |end| now.min(end))
+      .seconds()
+      .saturating_sub(self.start.seconds());
+    let (accrued, _) = self
+      .rate_per_second
+      .try_mul_floor(Decimal256::from_ratio(elapsed, 1u128))?
+      .truncate()?;
+    Ok(accrued)
+  }
+
+  /// Coins accrued by `now` minus those `already_claimed`, for streams whose claims are
+  /// tracked elsewhere.
+  pub fn claimable(&self, now: Timestamp, already_claimed: &CoinSet) -> MathResult<CoinSet> {
+    self.accrued(now)?.try_minus(already_claimed)
+  }
+
+  /// Coins accrued by `now` and not yet withdrawn.
+  pub fn withdrawable(&self, now: Timestamp) -> MathResult<CoinSet> {
+    Ok(self.claimable(now, &self.withdrawn)?.without_zeros())
+  }
+
+  /// Withdraw everything accrued by `now`, recording it and creating the message sending it
+  /// to the recipient.
+  ///
+  /// Requires something to be withdrawable.
+  pub fn withdraw(&mut self, now: Timestamp) -> XcosmResult<(CoinSet, CosmosMsg)> {
+    let coins = self.withdrawable(now)?;
+    let msg = coins.send(&self.recipient)?;
+    self.withdrawn.try_plus_mut(&coins)?;
+    Ok((coins, msg))
+  }
+
+  /// Check if everything has accrued and been withdrawn as of `now`. Never true without an
+  /// `end`.
+  pub fn is_finished(&self, now: Timestamp) -> MathResult<bool> {
+    match self.end {
+      Some(end) if now >= end => Ok(self.withdrawable(now)?.is_empty()),
+      _ => Ok(false),
+    }
+  }
+}
+
+/// Length of each epoch of an [`EpochDistributor`], counted from `start`.
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum EpochPeriod {
+  Time { start: Timestamp, seconds: u64 },
+  Height { start: u64, blocks: u64 },
+}
+
+impl EpochPeriod {
+  /// Get the number of epochs completed as of `block`. Zero before `start`.
+  pub fn epochs_at(&self, block: &BlockInfo) -> MathResult<u64> {
+    let (elapsed, period) = match self {
+      EpochPeriod::Time { start, seconds } => (
+        block.time.seconds().saturating_sub(start.seconds()),
+        *seconds,
+      ),
+      EpochPeriod::Height { start, blocks } => (block.height.saturating_sub(*start), *blocks),
+    };
+    elapsed
+      .checked_div(period)
+      .ok_or_else(|| ValueError::DivideByZero {}.into())
+  }
+}
+
+/// Recurring distribution of a fixed emission once per epoch.
+#[cw_serde]
+pub struct EpochDistributor {
+  pub period: EpochPeriod,
+  /// Funds distributed for each epoch.
+  pub per_epoch: CoinSet,
+  pub distribution: Distribution,
+  /// Number of the last epoch distributed, where epoch 0 is before the first period ends.
+  #[serde(default)]
+  pub last_epoch: u64,
+  /// Maximum number of missed epochs paid out at once, with any beyond it skipped. All
+  /// missed epochs are paid if `None`.
+  #[serde(default)]
+  pub max_catch_up: Option<u64>,
+}
+
+impl EpochDistributor {
+  pub fn new(period: EpochPeriod, per_epoch: CoinSet, distribution: Distribution) -> Self {
+    Self {
+      period,
+      per_epoch,
+      distribution,
+      last_epoch: 0,
+      max_catch_up: None,
+    }
+  }
+
+  /// Limit the number of missed epochs paid out at once, e.g. 1 to skip missed epochs.
+  pub fn with_max_catch_up(mut self, max_catch_up: u64) -> Self {
+    self.max_catch_up = Some(max_catch_up);
+    self
+  }
+
+  /// Get the number of completed epochs not yet distributed as of `block`.
+  pub fn pending_epochs(&self, block: &BlockInfo) -> MathResult<u64> {
+    Ok(
+      self
+        .period
+        .epochs_at(block)?
+        .saturating_sub(self.last_epoch),
+    )
+  }
+
+  /// Get the funds that distributing as of `block` would pay out, after the catch-up limit.
+  pub fn pending_funds(&self, block: &BlockInfo) -> MathResult<CoinSet> {
+    let epochs = self.pending_epochs(block)?;
+    let epochs = self.max_catch_up.map_or(epochs, |max| epochs.min(max));
+    self.per_epoch.try_mul_floor(Uint128::from(epochs))
+  }
+
+  /// Distribute the emission for every pending epoch from `from`, recording the current epoch
+  /// as distributed. Returns no messages if no epoch has completed since the last
+  /// distribution.
+  pub fn distribute(&mut self, from: &Addr, block: &BlockInfo) -> XcosmResult<Vec<CosmosMsg>> {
+    let funds = self.pending_funds(block)?.without_zeros();
+    let epoch = self.period.epochs_at(block)?;
+    let msgs = match funds.is_empty() {
+      true => vec![],
+      false => self.distribution.distribute_coins(from, &funds)?,
+    };
+    self.last_epoch = self.last_epoch.max(epoch);
+    Ok(msgs)
+  }
+}
+
+/// Spending limit of `cap` per period of `period_seconds`, starting at `period_start`.
+#[cw_serde]
+pub struct Budget {
+  pub cap: CoinSet,
+  pub period_seconds: u64,
+  /// Start of the current period.
+  pub period_start: Timestamp,
+  /// Coins spent in the current period.
+  pub spent: CoinSet,
+}
+
+impl Budget {
+  pub fn new(cap: CoinSet, period_seconds: u64, start: Timestamp) -> Self {
+    Self {
+      cap,
+      period_seconds,
+      period_start: start,
+      spent: CoinSet::default(),
+    }
+  }
+
+  /// Get the start of the period containing `now`. Periods never move backwards.
+  pub fn period_start_at(&self, now: Timestamp) -> MathResult<Timestamp> {
+    let elapsed = now.seconds().saturating_sub(self.period_start.seconds());
+    let periods = elapsed
+      .checked_div(self.period_seconds)
+      .ok_or(ValueError::DivideByZero {})?;
+    Ok(
+      self
+        .period_start
+        .plus_seconds(periods * self.period_seconds),
+    )
+  }
+
+  /// Get the coins spent in the period containing `now`.
+  pub fn spent_at(&self, now: Timestamp) -> MathResult<CoinSet> {
+    match self.period_start_at(now)? == self.period_start {
+      true => Ok(self.spent.clone()),
+      false => Ok(CoinSet::default()),
+    }
+  }
+
+  /// Get the coins left to spend in the period containing `now`.
+  pub fn remaining(&self, now: Timestamp) -> MathResult<CoinSet> {
+    Ok(self.cap.saturating_minus(&self.spent_at(now)?, true))
+  }
+
+  /// Record spending `coins` at `now`, starting a new period if the current one has ended.
+  ///
+  /// Fails without recording anything if the spending would exceed the cap, including for
+  /// denoms not in the cap.
+  pub fn try_spend(&mut self, coins: &CoinSet, now: Timestamp) -> XcosmResult {
+    let mut spent = self.spent_at(now)?;
+    spent.try_plus_mut(coins)?;
+    let spent = spent.without_zeros();
+    if !self.cap.shortfall(&spent).is_empty() {
+      return Err(
+        FundError::BudgetExceeded {
+          remaining: self.remaining(now)?.to_string(),
+        }
+        .into(),
+      );
+    }
+    self.period_start = self.period_start_at(now)?;
+    self.spent = spent;
+    Ok(())
+  }
+
+  /// Require `requestor` to be authorized and record spending `coins` at `now`. See
+  /// [`Budget::try_spend`].
+  pub fn try_spend_authorized(
+    &mut self,
+    authorized: &Authorized,
+    requestor: &Addr,
+    coins: &CoinSet,
+    now: Timestamp,
+  ) -> XcosmResult {
+    authorized.authorize(requestor)?;
+    self.try_spend(coins, now)
+  }
+}
+
+/// Contributions of a single denom by each address, giving each contributor a share of the
+/// pool proportional to its contribution.
+#[cw_serde]
+pub struct Pool {
+  pub denom: String,
+  pub contributions: BTreeMap<Addr, Uint128>,
+  pub total: Uint128,
+}
+
+impl Pool {
+  pub fn new(denom: impl Into<String>) -> Self {
+    Self {
+      denom: denom.into(),
+      contributions: BTreeMap::new(),
+      total: Uint128::zero(),
+    }
+  }
+
+  /// Record a contribution of `amount` from `addr`, returning its new total contribution.
+  pub fn contribute(&mut self, addr: Addr, amount: Uint128) -> MathResult<Uint128> {
+    let total = self.total.checked_add(amount)?;
+    let contribution = self.contribution_of(&addr).checked_add(amount)?;
+    self.contributions.insert(addr, contribution);
+    self.total = total;
+    Ok(contribution)
+  }
+
+  /// Record the pool denom sent with a message as a contribution from the sender, returning
+  /// its new total contribution.
+  ///
+  /// Requires the funds to contain only the pool denom.
+  pub fn contribute_funds(&mut self, info: &MessageInfo) -> XcosmResult<Uint128> {
+    let funds = info.fund_set()?.without_zeros();
+    funds.expect_only_denoms(&DenomFilter::allow([&self.denom]))?;
+    let amount = *funds.expect_coin(&Coin::new(1u128, &self.denom))?;
+    Ok(self.contribute(info.sender.clone(), amount)?)
+  }
+
+  /// Remove `amount` of the contribution of `addr`, returning its remaining contribution.
+  /// Contributors with nothing left are removed.
+  pub fn withdraw(&mut self, addr: &Addr, amount: Uint128) -> MathResult<Uint128> {
+    let contribution = self.contribution_of(addr).checked_sub(amount)?;
+    self.total = self.total.checked_sub(amount)?;
+    match contribution.is_zero() {
+      true => self.contributions.remove(addr),
+      false => self.contributions.insert(addr.clone(), contribution),
+    };
+    Ok(contribution)
+  }
+
+  /// Withdraw the full contribution of `addr` and create the message returning it.
+  ///
+  /// Requires a non-zero contribution.
+  pub fn refund(&mut self, addr: &Addr) -> XcosmResult<CosmosMsg> {
+    let amount = self.contribution_of(addr);
+    let msg = CoinSet::from_iter_summed([Coin::new(amount, &self.denom)])?.send(addr)?;
+    self.withdraw(addr, amount)?;
+    Ok(msg)
+  }
+
+  pub fn contribution_of(&self, addr: &Addr) -> Uint128 {
+    self.contributions.get(addr).copied().unwrap_or_default()
+  }
+
+  /// Get the fraction of the pool owned by `addr`, zero if the pool is empty.
+  pub fn share_of(&self, addr: &Addr) -> MathResult<Decimal> {
+    match self.total.is_zero() {
+      true => Ok(Decimal::zero()),
+      false => Ok(
+        Decimal::checked_from_ratio(self.contribution_of(addr), self.total)
+          .map_err(|_| ContainerError::Overflow {})?,
+      ),
+    }
+  }
+
+  /// Get a distribution paying out pro-rata to contributions. Weights are the exact
+  /// contributions, so no precision is lost to rounding shares into bps.
+  pub fn distribution(&self) -> WeightedDistribution {
+    WeightedDistribution::new(self.contributions.clone())
+  }
+
+  /// Distribute `payout` from `from` pro-rata to contributions.
+  pub fn distribute_coins(&self, from: &Addr, payout: &CoinSet) -> XcosmResult<Vec<CosmosMsg>> {
+    self.distribution().distribute_coins(from, payout)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::sent_outputs;
+  use crate::XcosmError;
+
+  fn addr(addr: &str) -> Addr {
+    Addr::unchecked(addr)
+  }
+
+  fn coins(coins: &str) -> CoinSet {
+    coins.parse().unwrap()
+  }
+
+  fn dust_distribution(remainder: RemainderPolicy) -> Distribution {
+    Distribution::new(BTreeMap::from([
+      (addr("a"), Claim::new(Bps::unbounded(9990))),
+      (addr("b"), Claim::new(Bps::unbounded(10))),
+    ]))
+    .with_remainder_policy(remainder)
+    .with_min_payout(coins("5uatom"))
+  }
+
+  fn allocated(allocation: &Allocation, to: &str) -> Option<CoinSet> {
+    allocation
+      .iter()
+      .find(|(addr, _)| addr.as_str() == to)
+      .map(|(_, coins)| coins.clone())
+  }
+
+  #[test]
+  fn min_payout_first_claim() {
+    let allocation = dust_distribution(RemainderPolicy::FirstClaim)
+      .compute_distribution(&coins("1000uatom"))
+      .unwrap();
+    assert_eq!(allocation, vec![(addr("a"), coins("1000uatom"))]);
+  }
+
+  #[test]
+  fn min_payout_largest_claim() {
+    let allocation = dust_distribution(RemainderPolicy::LargestClaim)
+      .compute_distribution(&coins("1000uatom"))
+      .unwrap();
+    assert_eq!(allocation, vec![(addr("a"), coins("1000uatom"))]);
+  }
+
+  #[test]
+  fn min_payout_redistribute() {
+    let allocation = dust_distribution(RemainderPolicy::Redistribute)
+      .compute_distribution(&coins("1000uatom"))
+      .unwrap();
+    assert_eq!(allocation, vec![(addr("a"), coins("1000uatom"))]);
+  }
+
+  #[test]
+  fn min_payout_burn() {
+    let (allocation, rem) = dust_distribution(RemainderPolicy::Burn)
+      .compute_distribution_with_remainder(&coins("1000uatom"))
+      .unwrap();
+    assert_eq!(allocation, vec![(addr("a"), coins("999uatom"))]);
+    assert_eq!(rem, coins("1uatom"));
+  }
+
+  #[test]
+  fn min_payout_specific_address() {
+    let allocation = dust_distribution(RemainderPolicy::SpecificAddress(addr("x")))
+      .compute_distribution(&coins("1000uatom"))
+      .unwrap();
+    assert_eq!(allocated(&allocation, "a"), Some(coins("999uatom")));
+    assert_eq!(allocated(&allocation, "b"), None);
+    assert_eq!(allocated(&allocation, "x"), Some(coins("1uatom")));
+  }
+
+  #[test]
+  fn min_payout_specific_address_below_min_terminates() {
+    let distribution = Distribution::new(BTreeMap::from([(
+      addr("a"),
+      Claim::new(Bps::unbounded(9999)),
+    )]))
+    .with_remainder_policy(RemainderPolicy::SpecificAddress(addr("x")))
+    .with_min_payout(coins("5uatom"));
+    let allocation = distribution
+      .compute_distribution(&coins("1000uatom"))
+      .unwrap();
+    assert_eq!(allocated(&allocation, "a"), Some(coins("999uatom")));
+    assert_eq!(allocated(&allocation, "x"), Some(coins("1uatom")));
+  }
+
+  fn block(height: u64) -> BlockInfo {
+    BlockInfo {
+      height,
+      time: Timestamp::from_seconds(height * 5),
+      chain_id: "test".to_string(),
+    }
+  }
+
+  #[test]
+  fn epoch_distribute_failure_keeps_last_epoch() {
+    let period = EpochPeriod::Height {
+      start: 0,
+      blocks: 10,
+    };
+    let mut distributor = EpochDistributor::new(
+      period,
+      coins("100uatom"),
+      Distribution::new(BTreeMap::new()),
+    );
+    assert!(distributor.distribute(&addr("from"), &block(25)).is_err());
+    assert_eq!(distributor.last_epoch, 0);
+    distributor.distribution =
+      Distribution::new(BTreeMap::from([(addr("a"), Claim::new(Bps::MAX))]));
+    assert_eq!(
+      distributor
+        .distribute(&addr("from"), &block(25))
+        .unwrap()
+        .len(),
+      1
+    );
+    assert_eq!(distributor.last_epoch, 2);
+    assert!(distributor
+      .distribute(&addr("from"), &block(29))
+      .unwrap()
+      .is_empty());
+  }
+
+  #[test]
+  fn estimate_outputs_matches_sends() {
+    let distribution = Distribution::new(BTreeMap::from([
+      (addr("a"), Claim::new(Bps::unbounded(6000))),
+      (addr("b"), Claim::new(Bps::unbounded(4000))),
+      (addr("c"), Claim::new(Bps::ZERO)),
+    ]))
+    .with_override(addr("d"), "uosmo", Bps::unbounded(0));
+    assert_eq!(distribution.estimate_outputs(), 2);
+    let msgs = distribution
+      .distribute_coins(&addr("from"), &coins("1000uatom,500uosmo"))
+      .unwrap();
+    assert_eq!(sent_outputs(&msgs).len(), distribution.estimate_outputs());
+  }
+
+  #[test]
+  fn distribution_json_formats() {
+    let legacy: Distribution = cosmwasm_std::from_json(r#"{"a":6000,"b":4000}"#).unwrap();
+    let distribution = Distribution::new(BTreeMap::from([
+      (addr("a"), Claim::new(Bps::unbounded(6000))),
+      (addr("b"), Claim::new(Bps::unbounded(4000))),
+    ]));
+    assert_eq!(legacy, distribution);
+    let distribution = distribution.with_remainder_policy(RemainderPolicy::Burn);
+    let json = cosmwasm_std::to_json_vec(&distribution).unwrap();
+    assert_eq!(
+      cosmwasm_std::from_json::<Distribution>(&json).unwrap(),
+      distribution
+    );
+    let msg: DistributionMsg = cosmwasm_std::from_json(r#"{"a":10000}"#).unwrap();
+    assert_eq!(msg.claims.len(), 1);
+    let msg: DistributionMsg =
+      cosmwasm_std::from_json(r#"{"claims":{"a":10000},"remainder":"burn"}"#).unwrap();
+    assert_eq!(msg.remainder, RemainderPolicy::Burn);
+    assert!(cosmwasm_std::from_json::<Distribution>(r#"{"claims":{"a":1},"extra":1}"#).is_err());
+  }
+
+  #[test]
+  fn payment_stream_fractional_rate_until_end() {
+    let rate = DecCoinSet::new(BTreeMap::from([(
+      "uatom".to_string(),
+      Decimal256::percent(250),
+    )]));
+    let mut stream = PaymentStream::new(addr("r"), rate, Timestamp::from_seconds(100))
+      .with_end(Timestamp::from_seconds(110));
+    assert_eq!(
+      stream.accrued(Timestamp::from_seconds(103)).unwrap(),
+      coins("7uatom")
+    );
+    let (withdrawn, _) = stream.withdraw(Timestamp::from_seconds(103)).unwrap();
+    assert_eq!(withdrawn, coins("7uatom"));
+    assert!(!stream.is_finished(Timestamp::from_seconds(200)).unwrap());
+    assert_eq!(
+      stream.withdrawable(Timestamp::from_seconds(200)).unwrap(),
+      coins("18uatom")
+    );
+    stream.withdraw(Timestamp::from_seconds(200)).unwrap();
+    assert!(stream.is_finished(Timestamp::from_seconds(200)).unwrap());
+    assert!(stream.withdraw(Timestamp::from_seconds(300)).is_err());
+  }
+
+  #[test]
+  fn payment_stream_claimable_over_time() {
+    let stream = PaymentStream::new(
+      addr("r"),
+      coins("10uatom,3uosmo"),
+      Timestamp::from_seconds(1000),
+    );
+    assert!(stream
+      .claimable(Timestamp::from_seconds(900), &CoinSet::default())
+      .unwrap()
+      .without_zeros()
+      .is_empty());
+    assert_eq!(
+      stream
+        .claimable(Timestamp::from_seconds(1010), &CoinSet::default())
+        .unwrap(),
+      coins("100uatom,30uosmo")
+    );
+    // an earlier claim at 1010, then again at 1025
+    let claimed = coins("100uatom,30uosmo");
+    assert_eq!(
+      stream
+        .claimable(Timestamp::from_seconds(1025), &claimed)
+        .unwrap(),
+      coins("150uatom,45uosmo")
+    );
+    // a partial claim of one denom leaves the rest claimable
+    let claimed = coins("250uatom");
+    assert_eq!(
+      stream
+        .claimable(Timestamp::from_seconds(1025), &claimed)
+        .unwrap()
+        .without_zeros(),
+      coins("75uosmo")
+    );
+    // claiming more than accrued is an error
+    assert!(stream
+      .claimable(Timestamp::from_seconds(1010), &coins("101uatom"))
+      .is_err());
+  }
+
+  fn allocated_total(allocation: &Allocation) -> CoinSet {
+    CoinSet::try_sum(allocation.iter().map(|(_, coins)| coins)).unwrap()
+  }
+
+  #[test]
+  fn logged_allocation_sums_to_input() {
+    let distribution = Distribution::new(BTreeMap::from([
+      (addr("a"), Claim::new(Bps::unbounded(3333))),
+      (addr("b"), Claim::new(Bps::unbounded(3333))),
+      (addr("c"), Claim::new(Bps::unbounded(3334))),
+    ]))
+    .with_override(addr("a"), "uosmo", Bps::unbounded(5000))
+    .with_override(addr("c"), "uosmo", Bps::unbounded(1666));
+    let funds = coins("1001uatom,77uosmo");
+    let (_, allocation) = distribution
+      .distribute_coins_logged(&addr("from"), &funds)
+      .unwrap();
+    assert_eq!(allocated_total(&allocation), funds);
+  }
+
+  #[test]
+  fn even_split_uses_up_funds() {
+    let half = Claim::new(Bps::unbounded(MAX_BPS / 2));
+    assert_eq!(half.claim_amount(1000).unwrap(), 500);
+    assert_eq!(
+      half.claim(&coins("1000utoken")).unwrap(),
+      coins("500utoken")
+    );
+    let distribution = Distribution::new(BTreeMap::from([(addr("a"), half), (addr("b"), half)]));
+    let (allocation, rem) = distribution
+      .compute_distribution_with_remainder(&coins("1000utoken"))
+      .unwrap();
+    assert_eq!(
+      allocation,
+      vec![
+        (addr("a"), coins("500utoken")),
+        (addr("b"), coins("500utoken")),
+      ]
+    );
+    assert!(rem.is_empty());
+    assert_eq!(allocated_total(&allocation), coins("1000utoken"));
+  }
+
+  #[test]
+  fn breakdown_matches_sends() {
+    let distribution = Distribution::new(BTreeMap::from([
+      (addr("a"), Claim::new(Bps::unbounded(5000))),
+      (addr("b"), Claim::new(Bps::unbounded(3000))),
+      (addr("c"), Claim::new(Bps::unbounded(2000))),
+    ]))
+    .with_remainder_policy(RemainderPolicy::LargestClaim);
+    let funds = coins("999uatom,7uosmo");
+    let (msgs, allocation) = distribution
+      .distribute_coins_logged(&addr("from"), &funds)
+      .unwrap();
+    assert_eq!(allocated_total(&allocation), funds);
+    let sent: Vec<(String, CoinSet)> = allocation
+      .into_iter()
+      .map(|(addr, coins)| (addr.to_string(), coins))
+      .collect();
+    assert_eq!(sent_outputs(&msgs), sent);
+  }
+
+  fn info(funds: &str) -> MessageInfo {
+    cosmwasm_std::testing::message_info(&addr("sender"), &coins(funds).into_vec())
+  }
+
+  #[test]
+  fn expect_funds_over_and_under_payment() {
+    let expected = || vec![Coin::new(100u128, "uatom")];
+    let over = info("150uatom");
+    assert!(over.expect_funds(expected()).is_ok());
+    assert_eq!(
+      over.expect_funds_at_least(expected()).unwrap(),
+      coins("50uatom")
+    );
+    assert!(over.expect_funds_exact(expected()).is_err());
+    let under = info("50uatom");
+    assert!(under.expect_funds(expected()).is_err());
+    assert!(under.expect_funds_at_least(expected()).is_err());
+    assert!(under.expect_funds_exact(expected()).is_err());
+    let exact = info("100uatom");
+    assert!(exact.expect_funds(expected()).is_ok());
+    assert!(exact.expect_funds_exact(expected()).is_ok());
+    let extra = info("100uatom,1uosmo");
+    assert!(extra.expect_funds(expected()).is_ok());
+    assert_eq!(
+      extra.expect_funds_at_least(expected()).unwrap(),
+      coins("1uosmo")
+    );
+    assert!(matches!(
+      extra.expect_funds_exact(expected()),
+      Err(XcosmError::Coin(CoinError::UnexpectedDenom { denom })) if denom == "uosmo"
+    ));
+  }
+
+  #[test]
+  fn delegate_target() {
+    let distribution = Distribution::new(BTreeMap::from([
+      (addr("a"), Claim::new(Bps::unbounded(5000))),
+      (addr("b"), Claim::new(Bps::unbounded(5000))),
+    ]))
+    .with_target(
+      addr("b"),
+      ClaimTarget::Delegate {
+        validator: "val".to_string(),
+      },
+    );
+    let json = cosmwasm_std::to_json_vec(&distribution).unwrap();
+    assert_eq!(
+      cosmwasm_std::from_json::<Distribution>(&json).unwrap(),
+      distribution
+    );
+    let res = distribution.distribute_coins(&addr("c"), &coins("10uatom"));
+    #[cfg(feature = "staking")]
+    assert!(res.unwrap().contains(
+      &StakingMsg::Delegate {
+        validator: "val".to_string(),
+        amount: Coin::new(5u128, "uatom"),
+      }
+      .into()
+    ));
+    #[cfg(not(feature = "staking"))]
+    assert!(matches!(
+      res,
+      Err(XcosmError::Fund(FundError::StakingDisabled {}))
+    ));
+  }
 }
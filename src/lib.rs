@@ -1,14 +1,44 @@
 #![deny(warnings)]
+/// Impl generators shared by the coin set types.
+#[macro_use]
+mod macros;
+
+pub mod asset;
 pub mod auth;
+#[cfg(all(feature = "protobuf", feature = "cosmwasm_2_0"))]
+pub mod authz;
 pub mod coin;
+pub mod coin256;
+pub mod coin_delta;
+pub mod dec_coin;
 pub mod error;
 pub mod fund;
 pub mod math;
+/// Protobuf mirrors of the Cosmos SDK types used for `Any` messages and gRPC queries.
+#[cfg(feature = "protobuf")]
+mod proto;
+#[cfg(feature = "cosmwasm_2_0")]
+pub mod query;
+#[cfg(feature = "storage")]
+pub mod storage;
+/// Property-testing strategies for the core types.
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod validate;
 
+pub use asset::*;
 pub use auth::*;
+#[cfg(all(feature = "protobuf", feature = "cosmwasm_2_0"))]
+pub use authz::*;
 pub use coin::*;
+pub use coin256::*;
+pub use coin_delta::*;
+pub use dec_coin::*;
 pub use error::*;
 pub use fund::*;
 pub use math::*;
+#[cfg(feature = "cosmwasm_2_0")]
+pub use query::*;
+#[cfg(feature = "storage")]
+pub use storage::*;
 pub use validate::*;
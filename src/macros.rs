@@ -0,0 +1,367 @@
+/// Implement the container machinery shared by the coin sets with wide amounts: construction,
+/// expectations, serde as a list of `$coin`, and checked addition and subtraction.
+///
+/// `$set` must be a newtype over `BTreeMap<String, $amount>` dereferencing to the map, and
+/// `$coin` a struct with `denom` and `amount` fields. The set must also provide its own
+/// `shortfall_of`, since narrowing the shortfall into a
+/// [`CoinShortfall`](crate::CoinShortfall) depends on the amount type.
+macro_rules! coin_set_impls {
+  ($set:ident, $coin:ident, $amount:ty) => {
+    impl $set {
+      #[doc = concat!("Create a new [`", stringify!($set), "`] from a map.")]
+      pub fn new(coins: ::std::collections::BTreeMap<String, $amount>) -> Self {
+        $set(coins)
+      }
+
+      /// Insert the amount into the set.
+      ///
+      /// Requires the denom to not already be present.
+      pub fn try_insert(
+        &mut self,
+        denom: &str,
+        amount: $amount,
+      ) -> $crate::XcosmResult<&mut $amount> {
+        match self.entry(denom.to_string()) {
+          ::std::collections::btree_map::Entry::Occupied(_) => Err(
+            $crate::CoinError::DuplicateDenom {
+              denom: denom.to_string(),
+            }
+            .into(),
+          ),
+          ::std::collections::btree_map::Entry::Vacant(entry) => Ok(entry.insert(amount)),
+        }
+      }
+
+      /// Get the coins as a list.
+      pub fn into_vec(self) -> Vec<$coin> {
+        self
+          .0
+          .into_iter()
+          .map(|(denom, amount)| $coin { denom, amount })
+          .collect()
+      }
+
+      /// Require coins to contain the expected coin in at least the expected amount.
+      pub fn expect_coin(&self, expected: &$coin) -> $crate::XcosmResult<&$amount> {
+        self
+          .get(&expected.denom)
+          .filter(|&amount| *amount >= expected.amount)
+          .ok_or_else(|| {
+            $crate::CoinError::Insufficient {
+              expected: format!("{}{}", expected.amount, expected.denom),
+              shortfall: self.shortfall_of(expected),
+            }
+            .into()
+          })
+      }
+
+      /// Require coins to contain all the expected coins in at least the expected amounts.
+      pub fn expect_coins(&self, expected: impl IntoIterator<Item=$coin>) -> $crate::XcosmResult {
+        expected
+          .into_iter()
+          .map(|c| self.expect_coin(&c))
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(())
+      }
+
+      /// Require coins to be empty.
+      pub fn expect_none(&self) -> $crate::XcosmResult {
+        if !self.is_empty() {
+          return Err($crate::CoinError::NotEmpty {}.into());
+        }
+        Ok(())
+      }
+
+      /// Require coins to not be empty.
+      pub fn expect_some(&self) -> $crate::XcosmResult<&Self> {
+        if self.is_empty() {
+          return Err($crate::CoinError::Empty {}.into());
+        }
+        Ok(self)
+      }
+
+      /// Check if coins contain the denom.
+      pub fn contains_denom(&self, denom: &str) -> bool {
+        self.contains_key(denom)
+      }
+    }
+
+    impl TryFrom<Vec<$coin>> for $set {
+      type Error = $crate::XcosmError;
+
+      /// Create the set from an unsorted list of coins.
+      ///
+      /// Requires the provided list to contain no duplicates.
+      fn try_from(raw: Vec<$coin>) -> $crate::XcosmResult<Self> {
+        let mut coins = $set::default();
+        for coin in raw {
+          coins.try_insert(&coin.denom, coin.amount)?;
+        }
+        Ok(coins)
+      }
+    }
+
+    impl ::serde::Serialize for $set {
+      fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use ::serde::ser::SerializeSeq;
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for (denom, amount) in self.iter() {
+          seq.serialize_element(&$coin {
+            denom: denom.clone(),
+            amount: *amount,
+          })?;
+        }
+        seq.end()
+      }
+    }
+
+    impl<'de> ::serde::Deserialize<'de> for $set {
+      fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw: Vec<$coin> = ::serde::Deserialize::deserialize(deserializer)?;
+        $set::try_from(raw).map_err(::serde::de::Error::custom)
+      }
+    }
+
+    impl ::schemars::JsonSchema for $set {
+      fn schema_name() -> String {
+        Vec::<$coin>::schema_name()
+      }
+
+      fn is_referenceable() -> bool {
+        Vec::<$coin>::is_referenceable()
+      }
+
+      /// Use the list schema to match the serialized form.
+      fn json_schema(gen: &mut ::schemars::gen::SchemaGenerator) -> ::schemars::schema::Schema {
+        Vec::<$coin>::json_schema(gen)
+      }
+    }
+
+    impl ::std::fmt::Display for $set {
+      fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(
+          f,
+          "{}",
+          ::serde_json::to_string(self).map_err(|_| ::std::fmt::Error)?
+        )
+      }
+    }
+
+    impl $crate::TryPlus<&$coin> for $set {
+      type Output = Self;
+      type Error = $crate::MathError;
+
+      fn try_plus(&self, other: &$coin) -> $crate::MathResult<Self> {
+        let mut res = self.clone();
+        $crate::TryPlusMut::try_plus_mut(&mut res, other)?;
+        Ok(res)
+      }
+    }
+
+    impl $crate::TryPlus<&$set> for $set {
+      type Output = Self;
+      type Error = $crate::MathError;
+
+      fn try_plus(&self, other: &$set) -> $crate::MathResult<Self> {
+        let mut res = self.clone();
+        $crate::TryPlusMut::try_plus_mut(&mut res, other)?;
+        Ok(res)
+      }
+    }
+
+    impl $crate::TryPlusMut<&$coin> for $set {
+      type Error = $crate::MathError;
+
+      fn try_plus_mut(&mut self, other: &$coin) -> $crate::MathResult {
+        let amount = self.entry(other.denom.clone()).or_default();
+        *amount = amount.checked_add(other.amount)?;
+        Ok(())
+      }
+    }
+
+    impl $crate::TryPlusMut<&$set> for $set {
+      type Error = $crate::MathError;
+
+      fn try_plus_mut(&mut self, other: &$set) -> $crate::MathResult {
+        for (denom, amount) in other.iter() {
+          let self_amount = self.entry(denom.clone()).or_default();
+          *self_amount = self_amount.checked_add(*amount)?;
+        }
+        Ok(())
+      }
+    }
+
+    impl $crate::StrictPlus<&$coin> for $set {
+      type Output = Self;
+      type Error = $crate::MathError;
+
+      fn strict_plus(&self, other: &$coin) -> $crate::MathResult<Self> {
+        if !self.contains_key(&other.denom) {
+          return Err(
+            $crate::ContainerError::Missing {
+              key: other.denom.clone(),
+            }
+            .into(),
+          );
+        }
+        $crate::TryPlus::try_plus(self, other)
+      }
+    }
+
+    impl $crate::StrictPlus<&$set> for $set {
+      type Output = Self;
+      type Error = $crate::MathError;
+
+      fn strict_plus(&self, other: &$set) -> $crate::MathResult<Self> {
+        if let Some(denom) = other.keys().find(|denom| !self.contains_key(*denom)) {
+          return Err($crate::ContainerError::Missing { key: denom.clone() }.into());
+        }
+        $crate::TryPlus::try_plus(self, other)
+      }
+    }
+
+    impl $crate::TryMinus<&$coin> for $set {
+      type Output = Self;
+      type Error = $crate::MathError;
+
+      fn try_minus(&self, other: &$coin) -> $crate::MathResult<Self> {
+        let mut res = self.clone();
+        $crate::TryMinusMut::try_minus_mut(&mut res, other)?;
+        Ok(res)
+      }
+    }
+
+    impl $crate::TryMinus<&$set> for $set {
+      type Output = Self;
+      type Error = $crate::MathError;
+
+      fn try_minus(&self, other: &$set) -> $crate::MathResult<Self> {
+        let mut res = self.clone();
+        $crate::TryMinusMut::try_minus_mut(&mut res, other)?;
+        Ok(res)
+      }
+    }
+
+    impl $crate::TryMinusMut<&$coin> for $set {
+      type Error = $crate::MathError;
+
+      fn try_minus_mut(&mut self, other: &$coin) -> $crate::MathResult {
+        if let Some(amount) = self.get_mut(&other.denom) {
+          *amount = amount
+            .checked_sub(other.amount)
+            .map_err(|_| $crate::ContainerError::Underflow {})?;
+        }
+        Ok(())
+      }
+    }
+
+    impl $crate::TryMinusMut<&$set> for $set {
+      type Error = $crate::MathError;
+
+      fn try_minus_mut(&mut self, other: &$set) -> $crate::MathResult {
+        for (denom, amount) in other.iter() {
+          if let Some(self_amount) = self.get_mut(denom) {
+            *self_amount = self_amount
+              .checked_sub(*amount)
+              .map_err(|_| $crate::ContainerError::Underflow {})?;
+          }
+        }
+        Ok(())
+      }
+    }
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use cosmwasm_std::Uint256;
+
+  use crate::{
+    Coin256, CoinError, CoinSet256, ContainerError, MathError, StrictPlus, TryMinus, TryPlus,
+    XcosmError,
+  };
+
+  fn set(coins: &[(u128, &str)]) -> CoinSet256 {
+    CoinSet256::new(
+      coins
+        .iter()
+        .map(|(amount, denom)| (denom.to_string(), Uint256::from(*amount)))
+        .collect(),
+    )
+  }
+
+  #[test]
+  fn json_round_trip() {
+    let coins = set(&[(2, "uosmo"), (1, "uatom")]);
+    let json = r#"[{"denom":"uatom","amount":"1"},{"denom":"uosmo","amount":"2"}]"#;
+    assert_eq!(serde_json::to_string(&coins).unwrap(), json);
+    assert_eq!(coins.to_string(), json);
+    assert_eq!(serde_json::from_str::<CoinSet256>(json).unwrap(), coins);
+    let dupe = r#"[{"denom":"uatom","amount":"1"},{"denom":"uatom","amount":"2"}]"#;
+    assert!(serde_json::from_str::<CoinSet256>(dupe).is_err());
+  }
+
+  #[test]
+  fn container() {
+    let mut coins = set(&[(1, "uatom")]);
+    assert!(matches!(
+      coins.try_insert("uatom", Uint256::one()),
+      Err(XcosmError::Coin(CoinError::DuplicateDenom { denom })) if denom == "uatom"
+    ));
+    coins.try_insert("uosmo", Uint256::from(2u8)).unwrap();
+    assert!(coins.contains_denom("uosmo"));
+    assert!(coins.expect_some().is_ok());
+    assert!(coins.expect_none().is_err());
+    assert!(CoinSet256::default().expect_none().is_ok());
+    assert!(CoinSet256::default().expect_some().is_err());
+    assert_eq!(
+      coins.clone().into_vec(),
+      vec![Coin256::new(1u8, "uatom"), Coin256::new(2u8, "uosmo")]
+    );
+    assert_eq!(
+      CoinSet256::try_from(coins.clone().into_vec()).unwrap(),
+      coins
+    );
+  }
+
+  #[test]
+  fn arithmetic() {
+    let coins = set(&[(5, "uatom")]);
+    assert_eq!(
+      coins.try_plus(&set(&[(1, "uatom"), (2, "uosmo")])).unwrap(),
+      set(&[(6, "uatom"), (2, "uosmo")])
+    );
+    assert!(matches!(
+      coins.strict_plus(&Coin256::new(1u8, "uosmo")),
+      Err(MathError::Container(ContainerError::Missing { key })) if key == "uosmo"
+    ));
+    assert_eq!(
+      coins.strict_plus(&set(&[(1, "uatom")])).unwrap(),
+      set(&[(6, "uatom")])
+    );
+    assert_eq!(
+      coins
+        .try_minus(&set(&[(2, "uatom"), (1, "uosmo")]))
+        .unwrap(),
+      set(&[(3, "uatom")])
+    );
+    assert!(matches!(
+      coins.try_minus(&Coin256::new(6u8, "uatom")),
+      Err(MathError::Container(ContainerError::Underflow {}))
+    ));
+  }
+
+  #[test]
+  fn expect_coins() {
+    let coins = set(&[(5, "uatom"), (1, "uosmo")]);
+    assert!(coins
+      .expect_coins(vec![Coin256::new(5u8, "uatom"), Coin256::new(1u8, "uosmo")])
+      .is_ok());
+    match coins.expect_coins(vec![Coin256::new(1u8, "uatom"), Coin256::new(2u8, "uosmo")]) {
+      Err(XcosmError::Coin(CoinError::Insufficient { expected, .. })) => {
+        assert_eq!(expected, "2uosmo");
+      }
+      res => panic!("unexpected {res:?}"),
+    }
+  }
+}
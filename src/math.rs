@@ -1,4 +1,11 @@
-use cosmwasm_std::Coin;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+  CheckedMultiplyFractionError, CheckedMultiplyRatioError, Coin, Coins, Decimal, Fraction, Isqrt,
+  Timestamp, Uint128, Uint256, Uint512,
+};
 
 use crate::CoinSet;
 
@@ -20,12 +27,56 @@ pub enum ContainerError {
 
   #[error("Underflow in math operation")]
   Underflow {},
+
+  #[error("Missing key in math operation: {key:?}")]
+  Missing { key: String },
+
+  #[error("Overflow in math operation on {denom:?}: {lhs} + {rhs}")]
+  DenomOverflow {
+    denom: String,
+    lhs: Uint128,
+    rhs: Uint128,
+  },
+
+  #[error("Underflow in math operation on {denom:?}: {lhs} - {rhs}")]
+  DenomUnderflow {
+    denom: String,
+    lhs: Uint128,
+    rhs: Uint128,
+  },
+}
+
+/// Add two amounts of `denom`, reporting the operands on overflow.
+pub(crate) fn add_amount(denom: &str, lhs: Uint128, rhs: Uint128) -> MathResult<Uint128> {
+  lhs.checked_add(rhs).map_err(|_| {
+    ContainerError::DenomOverflow {
+      denom: denom.to_string(),
+      lhs,
+      rhs,
+    }
+    .into()
+  })
+}
+
+/// Subtract two amounts of `denom`, reporting the operands on underflow.
+pub(crate) fn sub_amount(denom: &str, lhs: Uint128, rhs: Uint128) -> MathResult<Uint128> {
+  lhs.checked_sub(rhs).map_err(|_| {
+    ContainerError::DenomUnderflow {
+      denom: denom.to_string(),
+      lhs,
+      rhs,
+    }
+    .into()
+  })
 }
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
 pub enum ValueError {
   #[error("Divide by zero in math operation")]
   DivideByZero {},
+
+  #[error("Value out of range in math operation: {value}")]
+  OutOfRange { value: String },
 }
 
 impl From<cosmwasm_std::OverflowError> for MathError {
@@ -34,6 +85,360 @@ impl From<cosmwasm_std::OverflowError> for MathError {
   }
 }
 
+impl From<CheckedMultiplyFractionError> for MathError {
+  fn from(err: CheckedMultiplyFractionError) -> Self {
+    match err {
+      CheckedMultiplyFractionError::DivideByZero(_) => ValueError::DivideByZero {}.into(),
+      _ => ContainerError::Overflow {}.into(),
+    }
+  }
+}
+
+impl From<CheckedMultiplyRatioError> for MathError {
+  fn from(err: CheckedMultiplyRatioError) -> Self {
+    match err {
+      CheckedMultiplyRatioError::DivideByZero => ValueError::DivideByZero {}.into(),
+      CheckedMultiplyRatioError::Overflow => ContainerError::Overflow {}.into(),
+    }
+  }
+}
+
+/// Rounding direction for fractional amounts.
+#[cw_serde]
+#[derive(Copy, Default, Eq)]
+pub enum Rounding {
+  /// Round toward zero.
+  #[default]
+  Floor,
+  /// Round away from zero.
+  Ceil,
+  /// Round to nearest, with halves rounded away from zero.
+  HalfUp,
+}
+
+impl Rounding {
+  /// Multiply `amount` by `numerator / denominator`, rounding the result.
+  pub fn mul_ratio(
+    self,
+    amount: Uint128,
+    numerator: Uint128,
+    denominator: Uint128,
+  ) -> MathResult<Uint128> {
+    if denominator.is_zero() {
+      return Err(ValueError::DivideByZero {}.into());
+    }
+    let product = amount.full_mul(numerator);
+    let denominator = Uint256::from(denominator);
+    let (quotient, rem) = (product / denominator, product % denominator);
+    let round_up = match self {
+      Rounding::Floor => false,
+      Rounding::Ceil => !rem.is_zero(),
+      Rounding::HalfUp => rem >= denominator - rem,
+    };
+    let res = match round_up {
+      true => quotient.checked_add(Uint256::one())?,
+      false => quotient,
+    };
+    Uint128::try_from(res).map_err(|_| ContainerError::Overflow {}.into())
+  }
+
+  /// Multiply `amount` by `numerator / denominator` with 512-bit intermediates, rounding the
+  /// result.
+  pub fn mul_ratio256(
+    self,
+    amount: Uint256,
+    numerator: Uint256,
+    denominator: Uint256,
+  ) -> MathResult<Uint256> {
+    if denominator.is_zero() {
+      return Err(ValueError::DivideByZero {}.into());
+    }
+    let product = amount.full_mul(numerator);
+    let denominator = Uint512::from(denominator);
+    let (quotient, rem) = (product / denominator, product % denominator);
+    let round_up = match self {
+      Rounding::Floor => false,
+      Rounding::Ceil => !rem.is_zero(),
+      Rounding::HalfUp => rem >= denominator - rem,
+    };
+    let res = match round_up {
+      true => quotient.checked_add(Uint512::one())?,
+      false => quotient,
+    };
+    Uint256::try_from(res).map_err(|_| ContainerError::Overflow {}.into())
+  }
+}
+
+/// Exact `num / den` ratio applied with full 256-bit intermediate precision.
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub struct Ratio {
+  pub num: Uint128,
+  pub den: Uint128,
+}
+
+impl Ratio {
+  /// Create a new [`Ratio`].
+  ///
+  /// Requires `den` to be non-zero.
+  pub fn new(num: impl Into<Uint128>, den: impl Into<Uint128>) -> MathResult<Self> {
+    let (num, den) = (num.into(), den.into());
+    if den.is_zero() {
+      return Err(ValueError::DivideByZero {}.into());
+    }
+    Ok(Self { num, den })
+  }
+
+  /// Multiply `amount` by the ratio with the given rounding.
+  pub fn mul(&self, amount: Uint128, rounding: Rounding) -> MathResult<Uint128> {
+    rounding.mul_ratio(amount, self.num, self.den)
+  }
+
+  /// Multiply `amount` by the ratio, rounding down.
+  pub fn mul_floor(&self, amount: Uint128) -> MathResult<Uint128> {
+    self.mul(amount, Rounding::Floor)
+  }
+
+  /// Multiply `amount` by the ratio, rounding up.
+  pub fn mul_ceil(&self, amount: Uint128) -> MathResult<Uint128> {
+    self.mul(amount, Rounding::Ceil)
+  }
+
+  /// Multiply every amount in `coins` by the ratio, rounding down. Zero amounts are kept.
+  pub fn apply_floor(&self, coins: &CoinSet) -> MathResult<CoinSet> {
+    coins.try_mul_floor(*self)
+  }
+
+  /// Multiply every amount in `coins` by the ratio, rounding up. Zero amounts are kept.
+  pub fn apply_ceil(&self, coins: &CoinSet) -> MathResult<CoinSet> {
+    coins.try_mul_ceil(*self)
+  }
+}
+
+impl Fraction<Uint128> for Ratio {
+  fn numerator(&self) -> Uint128 {
+    self.num
+  }
+
+  fn denominator(&self) -> Uint128 {
+    self.den
+  }
+
+  fn inv(&self) -> Option<Self> {
+    match self.num.is_zero() {
+      true => None,
+      false => Some(Self {
+        num: self.den,
+        den: self.num,
+      }),
+    }
+  }
+}
+
+/// Integer square root, rounded down.
+pub fn isqrt(value: Uint256) -> Uint256 {
+  value.isqrt()
+}
+
+/// Geometric mean of `values`, rounded down so that `mean^n <= product`.
+///
+/// Any zero value gives zero. Requires `values` to be non-empty and their product to fit in a
+/// [`Uint512`], which always holds for up to four values.
+pub fn geometric_mean(values: &[Uint128]) -> MathResult<Uint128> {
+  let n = u32::try_from(values.len()).map_err(|_| ContainerError::Overflow {})?;
+  match values {
+    [] => Err(ValueError::DivideByZero {}.into()),
+    [value] => Ok(*value),
+    [a, b] => {
+      Ok(Uint128::try_from(isqrt(a.full_mul(*b))).map_err(|_| ContainerError::Overflow {})?)
+    }
+    _ => {
+      let product = values.iter().try_fold(Uint512::one(), |product, value| {
+        product.checked_mul(Uint512::from(*value))
+      })?;
+      // the mean never exceeds the largest value, so binary search below it
+      let (mut low, mut high) = (
+        Uint128::zero(),
+        values.iter().max().copied().unwrap_or_default(),
+      );
+      while low < high {
+        let mid = high - (high - low) / Uint128::new(2);
+        match Uint512::from(mid).checked_pow(n) {
+          Ok(pow) if pow <= product => low = mid,
+          _ => high = mid - Uint128::one(),
+        }
+      }
+      Ok(low)
+    }
+  }
+}
+
+/// Linearly interpolate from `start` to `end` by `t`, rounding toward `start`.
+///
+/// Requires `t` to be within `[0, 1]`.
+pub fn lerp(start: Uint128, end: Uint128, t: Decimal) -> MathResult<Uint128> {
+  if t > Decimal::one() {
+    return Err(
+      ValueError::OutOfRange {
+        value: t.to_string(),
+      }
+      .into(),
+    );
+  }
+  lerp_ratio(start, end, t.numerator(), t.denominator())
+}
+
+/// Linearly interpolate from `start` to `end` by `num / den`, rounding toward `start`.
+fn lerp_ratio(start: Uint128, end: Uint128, num: Uint128, den: Uint128) -> MathResult<Uint128> {
+  match end >= start {
+    true => Ok(start + Rounding::Floor.mul_ratio(end - start, num, den)?),
+    false => Ok(start - Rounding::Floor.mul_ratio(start - end, num, den)?),
+  }
+}
+
+/// Point on a [`Curve`].
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub struct CurvePoint {
+  pub x: u64,
+  pub y: Uint128,
+}
+
+/// Piecewise-linear curve through points sorted by strictly increasing `x`, e.g. a vesting
+/// schedule with `x` in seconds.
+///
+/// The curve is flat before the first point and after the last.
+#[cw_serde]
+pub struct Curve(Vec<CurvePoint>);
+
+impl Curve {
+  /// Create a new [`Curve`].
+  ///
+  /// Requires at least one point and strictly increasing `x`.
+  pub fn new(points: Vec<CurvePoint>) -> MathResult<Self> {
+    let curve = Self(points);
+    curve.validate()?;
+    Ok(curve)
+  }
+
+  pub fn points(&self) -> &[CurvePoint] {
+    &self.0
+  }
+
+  /// Require at least one point and strictly increasing `x`.
+  pub fn validate(&self) -> MathResult {
+    if self.0.is_empty() {
+      return Err(
+        ContainerError::Missing {
+          key: "points".to_string(),
+        }
+        .into(),
+      );
+    }
+    if let Some(pair) = self.0.windows(2).find(|pair| pair[0].x >= pair[1].x) {
+      return Err(
+        ValueError::OutOfRange {
+          value: pair[1].x.to_string(),
+        }
+        .into(),
+      );
+    }
+    Ok(())
+  }
+
+  /// Evaluate the curve at `x`, rounding toward the start of the segment.
+  pub fn evaluate(&self, x: u64) -> MathResult<Uint128> {
+    self.validate()?;
+    let next = self.0.partition_point(|point| point.x <= x);
+    match (next.checked_sub(1).map(|i| self.0[i]), self.0.get(next)) {
+      (None, Some(first)) => Ok(first.y),
+      (Some(last), None) => Ok(last.y),
+      (Some(a), Some(b)) => lerp_ratio(a.y, b.y, Uint128::from(x - a.x), Uint128::from(b.x - a.x)),
+      (None, None) => Err(
+        ContainerError::Missing {
+          key: "points".to_string(),
+        }
+        .into(),
+      ),
+    }
+  }
+}
+
+/// Point-in-time reading of a [`TimeWeightedAccumulator`].
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub struct AccumulatorSnapshot {
+  pub cumulative: Uint256,
+  pub time: Timestamp,
+}
+
+/// Running sum of `value * elapsed_seconds` for time-weighted averages such as TWAPs.
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub struct TimeWeightedAccumulator {
+  pub cumulative: Uint256,
+  pub value: Uint128,
+  pub last_update: Timestamp,
+}
+
+impl TimeWeightedAccumulator {
+  /// Create a new [`TimeWeightedAccumulator`] starting at `value` from `now`.
+  pub fn new(value: Uint128, now: Timestamp) -> Self {
+    Self {
+      cumulative: Uint256::zero(),
+      value,
+      last_update: now,
+    }
+  }
+
+  /// Get the cumulative sum at `now`, accruing the current value since the last update.
+  ///
+  /// Requires `now` to not be before the last update.
+  pub fn cumulative_at(&self, now: Timestamp) -> MathResult<Uint256> {
+    let elapsed = now
+      .seconds()
+      .checked_sub(self.last_update.seconds())
+      .ok_or_else(|| ValueError::OutOfRange {
+        value: now.to_string(),
+      })?;
+    Ok(self.cumulative.checked_add(self.value.full_mul(elapsed))?)
+  }
+
+  /// Accrue the current value up to `now`, then switch to `value`.
+  ///
+  /// Requires `now` to not be before the last update.
+  pub fn update(&mut self, value: Uint128, now: Timestamp) -> MathResult {
+    self.cumulative = self.cumulative_at(now)?;
+    self.value = value;
+    self.last_update = now;
+    Ok(())
+  }
+
+  /// Take a snapshot at `now` to average from later.
+  pub fn snapshot(&self, now: Timestamp) -> MathResult<AccumulatorSnapshot> {
+    Ok(AccumulatorSnapshot {
+      cumulative: self.cumulative_at(now)?,
+      time: now,
+    })
+  }
+
+  /// Get the time-weighted average value between `since` and `now`, rounded down.
+  ///
+  /// Requires `now` to be after the snapshot.
+  pub fn average_since(&self, since: &AccumulatorSnapshot, now: Timestamp) -> MathResult<Uint128> {
+    let elapsed = now.seconds().saturating_sub(since.time.seconds());
+    if elapsed == 0 {
+      return Err(ValueError::DivideByZero {}.into());
+    }
+    let sum = self
+      .cumulative_at(now)?
+      .checked_sub(since.cumulative)
+      .map_err(|_| ContainerError::Underflow {})?;
+    Uint128::try_from(sum / Uint256::from(elapsed)).map_err(|_| ContainerError::Overflow {}.into())
+  }
+}
+
+/// Checked addition, inserting denoms missing from `self`.
 pub trait TryPlus<T> {
   type Output;
   type Error;
@@ -47,17 +452,8 @@ impl TryPlus<&Coin> for CoinSet {
 
   fn try_plus(&self, other: &Coin) -> MathResult<Self> {
     let mut res = self.clone();
-    let mut is_err = false;
-    res
-      .entry(other.denom.clone())
-      .and_modify(|amount| match (*amount).checked_add(other.amount) {
-        Ok(amt) => *amount = amt,
-        Err(_) => is_err = true,
-      });
-    match is_err {
-      true => Err(ContainerError::Overflow {}.into()),
-      false => Ok(res),
-    }
+    res.try_plus_mut(other)?;
+    Ok(res)
   }
 }
 
@@ -67,22 +463,12 @@ impl TryPlus<&CoinSet> for CoinSet {
 
   fn try_plus(&self, other: &CoinSet) -> MathResult<Self> {
     let mut res = self.clone();
-    let mut is_err = false;
-    for (denom, amount) in other.iter() {
-      res.entry(denom.clone()).and_modify(|self_amount| {
-        match (*self_amount).checked_add(*amount) {
-          Ok(amt) => *self_amount = amt,
-          Err(_) => is_err = true,
-        }
-      });
-    }
-    match is_err {
-      true => Err(ContainerError::Overflow {}.into()),
-      false => Ok(res),
-    }
+    res.try_plus_mut(other)?;
+    Ok(res)
   }
 }
 
+/// Checked in-place addition, inserting denoms missing from `self`.
 pub trait TryPlusMut<T> {
   type Error;
 
@@ -93,17 +479,9 @@ impl TryPlusMut<&Coin> for CoinSet {
   type Error = MathError;
 
   fn try_plus_mut(&mut self, other: &Coin) -> MathResult {
-    let mut is_err = false;
-    self.entry(other.denom.clone()).and_modify(|amount| {
-      match (*amount).checked_sub(other.amount) {
-        Ok(amt) => *amount = amt,
-        Err(_) => is_err = true,
-      }
-    });
-    match is_err {
-      true => Err(ContainerError::Underflow {}.into()),
-      false => Ok(()),
-    }
+    let amount = self.entry(other.denom.clone()).or_default();
+    *amount = add_amount(&other.denom, *amount, other.amount)?;
+    Ok(())
   }
 }
 
@@ -111,19 +489,76 @@ impl TryPlusMut<&CoinSet> for CoinSet {
   type Error = MathError;
 
   fn try_plus_mut(&mut self, other: &CoinSet) -> MathResult {
-    let mut is_err = false;
     for (denom, amount) in other.iter() {
-      self.entry(denom.clone()).and_modify(|self_amount| {
-        match (*self_amount).checked_add(*amount) {
-          Ok(amt) => *self_amount = amt,
-          Err(_) => is_err = true,
-        }
-      });
+      let self_amount = self.entry(denom.clone()).or_default();
+      *self_amount = add_amount(denom, *self_amount, *amount)?;
     }
-    match is_err {
-      true => Err(ContainerError::Overflow {}.into()),
-      false => Ok(()),
+    Ok(())
+  }
+}
+
+/// Checked addition requiring every added denom to already be present in `self`.
+pub trait StrictPlus<T> {
+  type Output;
+  type Error;
+
+  fn strict_plus(&self, other: T) -> Result<Self::Output, Self::Error>;
+}
+
+impl StrictPlus<&Coin> for CoinSet {
+  type Output = Self;
+  type Error = MathError;
+
+  fn strict_plus(&self, other: &Coin) -> MathResult<Self> {
+    let mut res = self.clone();
+    res.strict_plus_mut(other)?;
+    Ok(res)
+  }
+}
+
+impl StrictPlus<&CoinSet> for CoinSet {
+  type Output = Self;
+  type Error = MathError;
+
+  fn strict_plus(&self, other: &CoinSet) -> MathResult<Self> {
+    let mut res = self.clone();
+    res.strict_plus_mut(other)?;
+    Ok(res)
+  }
+}
+
+/// Checked in-place addition requiring every added denom to already be present in `self`.
+///
+/// This is the strict counterpart of [`TryPlusMut`], which inserts missing denoms.
+pub trait StrictPlusMut<T> {
+  type Error;
+
+  fn strict_plus_mut(&mut self, other: T) -> Result<(), Self::Error>;
+}
+
+impl StrictPlusMut<&Coin> for CoinSet {
+  type Error = MathError;
+
+  fn strict_plus_mut(&mut self, other: &Coin) -> MathResult {
+    let amount = self
+      .get_mut(&other.denom)
+      .ok_or_else(|| ContainerError::Missing {
+        key: other.denom.clone(),
+      })?;
+    *amount = add_amount(&other.denom, *amount, other.amount)?;
+    Ok(())
+  }
+}
+
+impl StrictPlusMut<&CoinSet> for CoinSet {
+  type Error = MathError;
+
+  /// Leaves `self` unchanged if any denom is missing.
+  fn strict_plus_mut(&mut self, other: &CoinSet) -> MathResult {
+    if let Some(denom) = other.keys().find(|denom| !self.contains_key(*denom)) {
+      return Err(ContainerError::Missing { key: denom.clone() }.into());
     }
+    self.try_plus_mut(other)
   }
 }
 
@@ -140,17 +575,8 @@ impl TryMinus<&Coin> for CoinSet {
 
   fn try_minus(&self, other: &Coin) -> MathResult<Self> {
     let mut res = self.clone();
-    let mut is_err = false;
-    res
-      .entry(other.denom.clone())
-      .and_modify(|amount| match (*amount).checked_sub(other.amount) {
-        Ok(amt) => *amount = amt,
-        Err(_) => is_err = true,
-      });
-    match is_err {
-      true => Err(ContainerError::Underflow {}.into()),
-      false => Ok(res),
-    }
+    res.try_minus_mut(other)?;
+    Ok(res)
   }
 }
 
@@ -160,19 +586,8 @@ impl TryMinus<&CoinSet> for CoinSet {
 
   fn try_minus(&self, other: &CoinSet) -> MathResult<Self> {
     let mut res = self.clone();
-    let mut is_err = false;
-    for (denom, amount) in other.iter() {
-      res.entry(denom.clone()).and_modify(|self_amount| {
-        match (*self_amount).checked_sub(*amount) {
-          Ok(amt) => *self_amount = amt,
-          Err(_) => is_err = true,
-        }
-      });
-    }
-    match is_err {
-      true => Err(ContainerError::Underflow {}.into()),
-      false => Ok(res),
-    }
+    res.try_minus_mut(other)?;
+    Ok(res)
   }
 }
 
@@ -186,17 +601,10 @@ impl TryMinusMut<&Coin> for CoinSet {
   type Error = MathError;
 
   fn try_minus_mut(&mut self, other: &Coin) -> MathResult {
-    let mut is_err = false;
-    self.entry(other.denom.clone()).and_modify(|amount| {
-      match (*amount).checked_sub(other.amount) {
-        Ok(amt) => *amount = amt,
-        Err(_) => is_err = true,
-      }
-    });
-    match is_err {
-      true => Err(ContainerError::Underflow {}.into()),
-      false => Ok(()),
+    if let Some(amount) = self.get_mut(&other.denom) {
+      *amount = sub_amount(&other.denom, *amount, other.amount)?;
     }
+    Ok(())
   }
 }
 
@@ -204,18 +612,491 @@ impl TryMinusMut<&CoinSet> for CoinSet {
   type Error = MathError;
 
   fn try_minus_mut(&mut self, other: &CoinSet) -> MathResult {
-    let mut is_err = false;
     for (denom, amount) in other.iter() {
-      self.entry(denom.clone()).and_modify(|self_amount| {
-        match (*self_amount).checked_sub(*amount) {
-          Ok(amt) => *self_amount = amt,
-          Err(_) => is_err = true,
-        }
-      });
+      if let Some(self_amount) = self.get_mut(denom) {
+        *self_amount = sub_amount(denom, *self_amount, *amount)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+// Plain coin types are summed through a `CoinSet`, so results are sorted and duplicate
+// denoms are merged. `Coins` results drop zero amounts.
+
+/// Require both coins to share a denom.
+fn expect_same_denom(coin: &Coin, other: &Coin) -> MathResult {
+  if coin.denom != other.denom {
+    return Err(
+      ContainerError::Missing {
+        key: other.denom.clone(),
+      }
+      .into(),
+    );
+  }
+  Ok(())
+}
+
+impl TryPlus<&Coin> for Coin {
+  type Output = Self;
+  type Error = MathError;
+
+  /// Add a coin of the same denom.
+  fn try_plus(&self, other: &Coin) -> MathResult<Self> {
+    expect_same_denom(self, other)?;
+    Ok(Coin::new(
+      add_amount(&self.denom, self.amount, other.amount)?,
+      &self.denom,
+    ))
+  }
+}
+
+impl TryMinus<&Coin> for Coin {
+  type Output = Self;
+  type Error = MathError;
+
+  /// Subtract a coin of the same denom.
+  fn try_minus(&self, other: &Coin) -> MathResult<Self> {
+    expect_same_denom(self, other)?;
+    Ok(Coin::new(
+      sub_amount(&self.denom, self.amount, other.amount)?,
+      &self.denom,
+    ))
+  }
+}
+
+impl<T> TryPlus<T> for Vec<Coin>
+where CoinSet: TryPlusMut<T, Error=MathError>
+{
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_plus(&self, other: T) -> MathResult<Self> {
+    let mut res = CoinSet::from_iter_summed(self.clone())?;
+    res.try_plus_mut(other)?;
+    Ok(res.into_vec())
+  }
+}
+
+impl<T> TryMinus<T> for Vec<Coin>
+where CoinSet: TryMinusMut<T, Error=MathError>
+{
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_minus(&self, other: T) -> MathResult<Self> {
+    let mut res = CoinSet::from_iter_summed(self.clone())?;
+    res.try_minus_mut(other)?;
+    Ok(res.into_vec())
+  }
+}
+
+impl<T> TryPlus<T> for Coins
+where CoinSet: TryPlusMut<T, Error=MathError>
+{
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_plus(&self, other: T) -> MathResult<Self> {
+    let mut res = CoinSet::from_coins_ref(self);
+    res.try_plus_mut(other)?;
+    Ok(res.into())
+  }
+}
+
+impl<T> TryMinus<T> for Coins
+where CoinSet: TryMinusMut<T, Error=MathError>
+{
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_minus(&self, other: T) -> MathResult<Self> {
+    let mut res = CoinSet::from_coins_ref(self);
+    res.try_minus_mut(other)?;
+    Ok(res.into())
+  }
+}
+
+/// Checked multiplication with explicit rounding.
+pub trait TryMul<T> {
+  type Output;
+  type Error;
+
+  /// Multiply, rounding each result down.
+  fn try_mul_floor(&self, other: T) -> Result<Self::Output, Self::Error>;
+
+  /// Multiply, rounding each result up.
+  fn try_mul_ceil(&self, other: T) -> Result<Self::Output, Self::Error>;
+}
+
+/// Scale every amount in `coins` by `f`, keeping zero amounts.
+fn scale_coin_set(
+  coins: &CoinSet,
+  f: impl Fn(Uint128) -> Result<Uint128, CheckedMultiplyFractionError>,
+) -> MathResult<CoinSet> {
+  let mut res = coins.clone();
+  for (_, amount) in res.iter_mut() {
+    *amount = f(*amount)?;
+  }
+  Ok(res)
+}
+
+impl TryMul<Decimal> for CoinSet {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_mul_floor(&self, other: Decimal) -> MathResult<Self> {
+    scale_coin_set(self, |amount| amount.checked_mul_floor(other))
+  }
+
+  fn try_mul_ceil(&self, other: Decimal) -> MathResult<Self> {
+    scale_coin_set(self, |amount| amount.checked_mul_ceil(other))
+  }
+}
+
+/// Multiply by an exact `(numerator, denominator)` ratio.
+impl TryMul<(Uint128, Uint128)> for CoinSet {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_mul_floor(&self, other: (Uint128, Uint128)) -> MathResult<Self> {
+    if other.denominator().is_zero() {
+      return Err(ValueError::DivideByZero {}.into());
     }
-    match is_err {
-      true => Err(ContainerError::Underflow {}.into()),
-      false => Ok(()),
+    scale_coin_set(self, |amount| amount.checked_mul_floor(other))
+  }
+
+  fn try_mul_ceil(&self, other: (Uint128, Uint128)) -> MathResult<Self> {
+    if other.denominator().is_zero() {
+      return Err(ValueError::DivideByZero {}.into());
+    }
+    scale_coin_set(self, |amount| amount.checked_mul_ceil(other))
+  }
+}
+
+impl TryMul<Uint128> for CoinSet {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_mul_floor(&self, other: Uint128) -> MathResult<Self> {
+    scale_coin_set(self, |amount| Ok(amount.checked_mul(other)?))
+  }
+
+  /// Integer multiplication is exact, so this matches [`TryMul::try_mul_floor`].
+  fn try_mul_ceil(&self, other: Uint128) -> MathResult<Self> {
+    self.try_mul_floor(other)
+  }
+}
+
+/// Multiply by an exact `(numerator, denominator)` ratio with a 256-bit intermediate.
+impl TryMul<(Uint128, Uint128)> for Uint128 {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_mul_floor(&self, other: (Uint128, Uint128)) -> MathResult<Self> {
+    Rounding::Floor.mul_ratio(*self, other.0, other.1)
+  }
+
+  fn try_mul_ceil(&self, other: (Uint128, Uint128)) -> MathResult<Self> {
+    Rounding::Ceil.mul_ratio(*self, other.0, other.1)
+  }
+}
+
+impl TryMul<(Uint128, Uint128)> for Coin {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_mul_floor(&self, other: (Uint128, Uint128)) -> MathResult<Self> {
+    Ok(Coin::new(self.amount.try_mul_floor(other)?, &self.denom))
+  }
+
+  fn try_mul_ceil(&self, other: (Uint128, Uint128)) -> MathResult<Self> {
+    Ok(Coin::new(self.amount.try_mul_ceil(other)?, &self.denom))
+  }
+}
+
+/// Checked division with explicit rounding.
+pub trait TryDiv<T> {
+  type Output;
+  type Error;
+
+  /// Divide, rounding each result down.
+  fn try_div_floor(&self, other: T) -> Result<Self::Output, Self::Error>;
+
+  /// Divide, rounding each result up.
+  fn try_div_ceil(&self, other: T) -> Result<Self::Output, Self::Error>;
+}
+
+impl TryDiv<Uint128> for CoinSet {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_div_floor(&self, other: Uint128) -> MathResult<Self> {
+    self.try_mul_floor((Uint128::one(), other))
+  }
+
+  fn try_div_ceil(&self, other: Uint128) -> MathResult<Self> {
+    self.try_mul_ceil((Uint128::one(), other))
+  }
+}
+
+impl TryMul<Ratio> for CoinSet {
+  type Output = Self;
+  type Error = MathError;
+
+  fn try_mul_floor(&self, other: Ratio) -> MathResult<Self> {
+    self.try_mul_ratio(other.num, other.den, Rounding::Floor)
+  }
+
+  fn try_mul_ceil(&self, other: Ratio) -> MathResult<Self> {
+    self.try_mul_ratio(other.num, other.den, Rounding::Ceil)
+  }
+}
+
+// Operator impls panic on overflow/underflow the same way `Uint128` operators do. Use the
+// `Try*` traits above where failure must be handled.
+
+impl Add<&Coin> for CoinSet {
+  type Output = Self;
+
+  /// Add, panicking on overflow.
+  fn add(mut self, other: &Coin) -> Self {
+    self += other;
+    self
+  }
+}
+
+impl Add<&CoinSet> for CoinSet {
+  type Output = Self;
+
+  /// Add, panicking on overflow.
+  fn add(mut self, other: &CoinSet) -> Self {
+    self += other;
+    self
+  }
+}
+
+impl AddAssign<&Coin> for CoinSet {
+  /// Add in place, panicking on overflow.
+  fn add_assign(&mut self, other: &Coin) {
+    self
+      .try_plus_mut(other)
+      .unwrap_or_else(|err| panic!("{}", err))
+  }
+}
+
+impl AddAssign<&CoinSet> for CoinSet {
+  /// Add in place, panicking on overflow.
+  fn add_assign(&mut self, other: &CoinSet) {
+    self
+      .try_plus_mut(other)
+      .unwrap_or_else(|err| panic!("{}", err))
+  }
+}
+
+impl Sub<&Coin> for CoinSet {
+  type Output = Self;
+
+  /// Subtract, panicking on underflow.
+  fn sub(mut self, other: &Coin) -> Self {
+    self -= other;
+    self
+  }
+}
+
+impl Sub<&CoinSet> for CoinSet {
+  type Output = Self;
+
+  /// Subtract, panicking on underflow.
+  fn sub(mut self, other: &CoinSet) -> Self {
+    self -= other;
+    self
+  }
+}
+
+impl SubAssign<&Coin> for CoinSet {
+  /// Subtract in place, panicking on underflow.
+  fn sub_assign(&mut self, other: &Coin) {
+    self
+      .try_minus_mut(other)
+      .unwrap_or_else(|err| panic!("{}", err))
+  }
+}
+
+impl SubAssign<&CoinSet> for CoinSet {
+  /// Subtract in place, panicking on underflow.
+  fn sub_assign(&mut self, other: &CoinSet) {
+    self
+      .try_minus_mut(other)
+      .unwrap_or_else(|err| panic!("{}", err))
+  }
+}
+
+impl<'a> Sum<&'a Coin> for CoinSet {
+  /// Sum coins, panicking on overflow.
+  fn sum<I: Iterator<Item=&'a Coin>>(iter: I) -> Self {
+    CoinSet::try_sum(iter).unwrap_or_else(|err| panic!("{}", err))
+  }
+}
+
+impl<'a> Sum<&'a CoinSet> for CoinSet {
+  /// Sum sets, panicking on overflow.
+  fn sum<I: Iterator<Item=&'a CoinSet>>(iter: I) -> Self {
+    CoinSet::try_sum(iter).unwrap_or_else(|err| panic!("{}", err))
+  }
+}
+
+impl Sum for CoinSet {
+  /// Sum sets, panicking on overflow.
+  fn sum<I: Iterator<Item=CoinSet>>(iter: I) -> Self {
+    let mut res = CoinSet::default();
+    for coins in iter {
+      res += &coins;
+    }
+    res
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use proptest::prelude::*;
+
+  use super::*;
+  use crate::testing::{coin_set_strategy, ARBITRARY_MAX_AMOUNT};
+
+  #[test]
+  fn coin_mul_ratio_rounding() {
+    let coin = Coin::new(u128::MAX - 1, "uatom");
+    let ratio = (Uint128::new(2), Uint128::new(3));
+    let floor = coin.try_mul_floor(ratio).unwrap();
+    let ceil = coin.try_mul_ceil(ratio).unwrap();
+    assert_eq!(floor.amount, coin.amount.multiply_ratio(2u128, 3u128));
+    assert_eq!(ceil.amount, floor.amount + Uint128::one());
+    assert!(coin
+      .try_mul_floor((Uint128::new(3), Uint128::new(2)))
+      .is_err());
+    assert!(coin
+      .try_mul_floor((Uint128::one(), Uint128::zero()))
+      .is_err());
+  }
+
+  #[test]
+  fn uint128_mul_ratio_rounding() {
+    let amount = Uint128::new(10);
+    let ratio = (Uint128::new(1), Uint128::new(3));
+    assert_eq!(amount.try_mul_floor(ratio).unwrap(), Uint128::new(3));
+    assert_eq!(amount.try_mul_ceil(ratio).unwrap(), Uint128::new(4));
+    assert_eq!(
+      Uint128::MAX
+        .try_mul_floor((Uint128::MAX, Uint128::MAX))
+        .unwrap(),
+      Uint128::MAX
+    );
+    assert!(Uint128::MAX
+      .try_mul_ceil((Uint128::new(2), Uint128::one()))
+      .is_err());
+    assert!(amount
+      .try_mul_floor((Uint128::one(), Uint128::zero()))
+      .is_err());
+  }
+
+  fn coins(coins: &str) -> CoinSet {
+    coins.parse().unwrap()
+  }
+
+  #[test]
+  fn operators_match_try() {
+    let a = coins("100uatom,5uosmo");
+    let b = coins("40uatom,1ujuno");
+    let coin = Coin::new(5u128, "uosmo");
+    assert_eq!(a.clone() + &b, a.try_plus(&b).unwrap());
+    assert_eq!(a.clone() + &coin, a.try_plus(&coin).unwrap());
+    assert_eq!(a.clone() - &b, a.try_minus(&b).unwrap());
+    assert_eq!(a.clone() - &coin, a.try_minus(&coin).unwrap());
+    let mut c = a.clone();
+    c += &b;
+    c -= &b;
+    assert_eq!(c, a.try_plus(&b).unwrap().try_minus(&b).unwrap());
+    // the cases the operators panic on
+    assert!(coins("1uatom")
+      .try_plus(&Coin::new(u128::MAX, "uatom"))
+      .is_err());
+    assert!(coins("1uatom").try_minus(&coins("2uatom")).is_err());
+  }
+
+  #[test]
+  #[should_panic]
+  fn add_panics_on_overflow() {
+    let _ = coins("1uatom") + &Coin::new(u128::MAX, "uatom");
+  }
+
+  #[test]
+  #[should_panic]
+  fn add_assign_panics_on_overflow() {
+    let mut a = coins("1uatom");
+    a += &CoinSet::new([("uatom".to_string(), Uint128::MAX)].into());
+  }
+
+  #[test]
+  #[should_panic]
+  fn sub_panics_on_underflow() {
+    let _ = coins("1uatom") - &coins("2uatom");
+  }
+
+  #[test]
+  #[should_panic]
+  fn sub_assign_panics_on_underflow() {
+    let mut a = coins("1uatom");
+    a -= &Coin::new(2u128, "uatom");
+  }
+
+  fn coin_sets() -> impl Strategy<Value=(CoinSet, CoinSet)> {
+    (
+      coin_set_strategy(6, ARBITRARY_MAX_AMOUNT),
+      coin_set_strategy(6, ARBITRARY_MAX_AMOUNT),
+    )
+  }
+
+  proptest! {
+    #[test]
+    fn lenient_plus_minus_round_trip((a, b) in coin_sets()) {
+      let sum = a.try_plus(&b).unwrap();
+      prop_assert!(sum.covers(&a) && sum.covers(&b));
+      prop_assert_eq!(sum.try_minus(&b).unwrap().without_zeros(), a);
+    }
+
+    #[test]
+    fn lenient_minus_plus_round_trip((a, b) in coin_sets()) {
+      let total = a.try_plus(&b).unwrap();
+      let rest = total.try_minus(&b).unwrap();
+      prop_assert_eq!(rest.try_plus(&b).unwrap(), total);
+    }
+
+    #[test]
+    fn strict_plus_minus_round_trip((a, b) in coin_sets()) {
+      // every denom of `b` is present in `base`
+      let base = a.try_plus(&b).unwrap();
+      let mut sum = base.clone();
+      sum.strict_plus_mut(&b).unwrap();
+      prop_assert_eq!(base.strict_plus(&b).unwrap(), sum.clone());
+      sum.try_minus_mut(&b).unwrap();
+      prop_assert_eq!(sum, base);
+    }
+
+    #[test]
+    fn strict_plus_rejects_new_denoms((a, b) in coin_sets()) {
+      let mut sum = a.clone();
+      let res = sum.strict_plus_mut(&b);
+      match b.keys().all(|denom| a.contains_key(denom)) {
+        true => {
+          prop_assert!(res.is_ok());
+          prop_assert_eq!(sum, a.try_plus(&b).unwrap());
+        }
+        false => {
+          prop_assert!(res.is_err());
+          prop_assert_eq!(sum, a);
+        }
+      }
     }
   }
 }
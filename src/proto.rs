@@ -0,0 +1,126 @@
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Coin {
+  #[prost(string, tag = "1")]
+  pub denom: String,
+  #[prost(string, tag = "2")]
+  pub amount: String,
+}
+
+/// Bank `Input` and `Output`, which share the same fields.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BankIo {
+  #[prost(string, tag = "1")]
+  pub address: String,
+  #[prost(message, repeated, tag = "2")]
+  pub coins: Vec<Coin>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgMultiSend {
+  #[prost(message, repeated, tag = "1")]
+  pub inputs: Vec<BankIo>,
+  #[prost(message, repeated, tag = "2")]
+  pub outputs: Vec<BankIo>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PageRequest {
+  #[prost(bytes = "vec", tag = "1")]
+  pub key: Vec<u8>,
+  #[prost(uint64, tag = "2")]
+  pub offset: u64,
+  #[prost(uint64, tag = "3")]
+  pub limit: u64,
+  #[prost(bool, tag = "4")]
+  pub count_total: bool,
+  #[prost(bool, tag = "5")]
+  pub reverse: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PageResponse {
+  #[prost(bytes = "vec", tag = "1")]
+  pub next_key: Vec<u8>,
+  #[prost(uint64, tag = "2")]
+  pub total: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryTotalSupplyRequest {
+  #[prost(message, optional, tag = "1")]
+  pub pagination: Option<PageRequest>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryTotalSupplyResponse {
+  #[prost(message, repeated, tag = "1")]
+  pub supply: Vec<Coin>,
+  #[prost(message, optional, tag = "2")]
+  pub pagination: Option<PageResponse>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Any {
+  #[prost(string, tag = "1")]
+  pub type_url: String,
+  #[prost(bytes = "vec", tag = "2")]
+  pub value: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Timestamp {
+  #[prost(int64, tag = "1")]
+  pub seconds: i64,
+  #[prost(int32, tag = "2")]
+  pub nanos: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GenericAuthorization {
+  #[prost(string, tag = "1")]
+  pub msg: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SendAuthorization {
+  #[prost(message, repeated, tag = "1")]
+  pub spend_limit: Vec<Coin>,
+  #[prost(string, repeated, tag = "2")]
+  pub allow_list: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Grant {
+  #[prost(message, optional, tag = "1")]
+  pub authorization: Option<Any>,
+  #[prost(message, optional, tag = "2")]
+  pub expiration: Option<Timestamp>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgGrant {
+  #[prost(string, tag = "1")]
+  pub granter: String,
+  #[prost(string, tag = "2")]
+  pub grantee: String,
+  #[prost(message, optional, tag = "3")]
+  pub grant: Option<Grant>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgRevoke {
+  #[prost(string, tag = "1")]
+  pub granter: String,
+  #[prost(string, tag = "2")]
+  pub grantee: String,
+  #[prost(string, tag = "3")]
+  pub msg_type_url: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgExec {
+  #[prost(string, tag = "1")]
+  pub grantee: String,
+  #[prost(message, repeated, tag = "2")]
+  pub msgs: Vec<Any>,
+}
@@ -0,0 +1,60 @@
+use cosmwasm_std::{CustomQuery, QuerierWrapper, Uint128};
+#[cfg(feature = "protobuf")]
+use prost::Message;
+
+use crate::XcosmResult;
+#[cfg(feature = "protobuf")]
+use crate::{
+  proto::{PageRequest, QueryTotalSupplyRequest, QueryTotalSupplyResponse},
+  CoinError, CoinSet,
+};
+
+/// Page size used when querying total supply.
+#[cfg(feature = "protobuf")]
+pub const TOTAL_SUPPLY_PAGE_LIMIT: u64 = 100;
+
+/// Get the total supply of a denom.
+pub fn supply_of<C: CustomQuery>(querier: &QuerierWrapper<C>, denom: &str) -> XcosmResult<Uint128> {
+  Ok(querier.query_supply(denom)?.amount)
+}
+
+/// Get the total supply of every denom, following pagination until all pages are read.
+///
+/// Not supported natively in `cosmwasm_std`; queries `/cosmos.bank.v1beta1.Query/TotalSupply`
+/// over gRPC.
+#[cfg(feature = "protobuf")]
+pub fn total_supply<C: CustomQuery>(querier: &QuerierWrapper<C>) -> XcosmResult<CoinSet> {
+  let mut supply = CoinSet::default();
+  let mut key = vec![];
+  loop {
+    let req = QueryTotalSupplyRequest {
+      pagination: Some(PageRequest {
+        key,
+        limit: TOTAL_SUPPLY_PAGE_LIMIT,
+        ..Default::default()
+      }),
+    };
+    let res = querier.query_grpc(
+      "/cosmos.bank.v1beta1.Query/TotalSupply".to_string(),
+      req.encode_to_vec().into(),
+    )?;
+    let res =
+      QueryTotalSupplyResponse::decode(res.as_slice()).map_err(|err| CoinError::Unexpected {
+        msg: format!("unable to decode QueryTotalSupplyResponse: {}", err),
+      })?;
+    for coin in res.supply {
+      let amount = coin
+        .amount
+        .parse::<u128>()
+        .map_err(|err| CoinError::Invalid {
+          coin: format!("{}{}", coin.amount, coin.denom),
+          reason: format!("amount: {}", err),
+        })?;
+      supply.try_insert(&coin.denom, amount.into())?;
+    }
+    match res.pagination {
+      Some(page) if !page.next_key.is_empty() => key = page.next_key,
+      _ => return Ok(supply),
+    }
+  }
+}
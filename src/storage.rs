@@ -0,0 +1,282 @@
+use cosmwasm_std::{Addr, Api, BlockInfo, StdError, StdResult, Storage};
+use cw_storage_plus::{Item, Key, KeyDeserialize, Map, Prefixer, PrimaryKey};
+
+use crate::{
+  Authorized, Claim, CoinSet, Denom, Distribution, DistributionValidation, TryMinusMut, TryPlusMut,
+  XcosmResult,
+};
+
+impl<'a> PrimaryKey<'a> for Denom {
+  type Prefix = ();
+  type SubPrefix = ();
+  type Suffix = Self;
+  type SuperSuffix = Self;
+
+  fn key(&self) -> Vec<Key<'_>> {
+    vec![Key::Ref(self.as_bytes())]
+  }
+}
+
+impl<'a> Prefixer<'a> for Denom {
+  fn prefix(&self) -> Vec<Key<'_>> {
+    vec![Key::Ref(self.as_bytes())]
+  }
+}
+
+/// Denoms read back from keys are trusted, as they were validated before being stored.
+impl KeyDeserialize for Denom {
+  type Output = Denom;
+
+  const KEY_ELEMS: u16 = 1;
+
+  fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+    Ok(Denom::unchecked(
+      String::from_utf8(value).map_err(StdError::invalid_utf8)?,
+    ))
+  }
+}
+
+impl KeyDeserialize for &Denom {
+  type Output = Denom;
+
+  const KEY_ELEMS: u16 = 1;
+
+  fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+    Denom::from_vec(value)
+  }
+}
+
+/// Per-address coin balances.
+pub type BalanceMap = Map<&'static Addr, CoinSet>;
+
+/// Load the balance of an address, empty if none is stored.
+pub fn load_balance(storage: &dyn Storage, map: &BalanceMap, addr: &Addr) -> XcosmResult<CoinSet> {
+  Ok(map.may_load(storage, addr)?.unwrap_or_default())
+}
+
+/// Add coins to the balance of an address and get the new balance.
+pub fn credit_balance(
+  storage: &mut dyn Storage,
+  map: &BalanceMap,
+  addr: &Addr,
+  coins: &CoinSet,
+) -> XcosmResult<CoinSet> {
+  let mut balance = load_balance(storage, map, addr)?;
+  balance.try_plus_mut(coins)?;
+  map.save(storage, addr, &balance)?;
+  Ok(balance)
+}
+
+/// Remove coins from the balance of an address and get the new balance. Zero amounts are
+/// dropped and an empty balance is removed from storage.
+///
+/// Requires the balance to contain at least `coins`.
+pub fn debit_balance(
+  storage: &mut dyn Storage,
+  map: &BalanceMap,
+  addr: &Addr,
+  coins: &CoinSet,
+) -> XcosmResult<CoinSet> {
+  let mut balance = load_balance(storage, map, addr)?;
+  balance.expect_coins(coins)?;
+  balance.try_minus_mut(coins)?;
+  let balance = balance.without_zeros();
+  match balance.is_empty() {
+    true => map.remove(storage, addr),
+    false => map.save(storage, addr, &balance)?,
+  }
+  Ok(balance)
+}
+
+/// Stored [`Distribution`] validated on every write, so invalid claims never reach storage.
+pub struct DistributionStore {
+  item: Item<Distribution>,
+  opts: DistributionValidation,
+}
+
+impl DistributionStore {
+  /// Store under `key`, checking writes with the default [`DistributionValidation`].
+  pub const fn new(key: &'static str) -> Self {
+    Self::with_validation(
+      key,
+      DistributionValidation {
+        allow_zero_claims: false,
+        max_claims: None,
+        require_full: false,
+      },
+    )
+  }
+
+  /// Store under `key`, checking writes with `opts`.
+  pub const fn with_validation(key: &'static str, opts: DistributionValidation) -> Self {
+    Self {
+      item: Item::new(key),
+      opts,
+    }
+  }
+
+  pub fn load(&self, storage: &dyn Storage) -> XcosmResult<Distribution> {
+    Ok(self.item.load(storage)?)
+  }
+
+  pub fn may_load(&self, storage: &dyn Storage) -> XcosmResult<Option<Distribution>> {
+    Ok(self.item.may_load(storage)?)
+  }
+
+  /// Validate and save `distribution`. See [`Distribution::validate`].
+  pub fn save(
+    &self,
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    distribution: &Distribution,
+  ) -> XcosmResult {
+    distribution.validate(api, &self.opts)?;
+    Ok(self.item.save(storage, distribution)?)
+  }
+
+  /// Load, modify, then validate and save the distribution, returning the saved value.
+  pub fn update<F>(
+    &self,
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    f: F,
+  ) -> XcosmResult<Distribution>
+  where
+    F: FnOnce(Distribution) -> XcosmResult<Distribution>,
+  {
+    let distribution = f(self.load(storage)?)?;
+    self.save(storage, api, &distribution)?;
+    Ok(distribution)
+  }
+
+  /// Add a claimant or replace its claim, returning the saved distribution.
+  pub fn set_claim(
+    &self,
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    addr: Addr,
+    claim: Claim,
+  ) -> XcosmResult<Distribution> {
+    self.update(storage, api, |mut distribution| {
+      distribution.claims.insert(addr, claim);
+      Ok(distribution)
+    })
+  }
+
+  /// Remove a claimant and its overrides, returning the saved distribution.
+  pub fn remove_claim(
+    &self,
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    addr: &Addr,
+  ) -> XcosmResult<Distribution> {
+    self.update(storage, api, |mut distribution| {
+      distribution.claims.remove(addr);
+      distribution.overrides.remove(addr);
+      Ok(distribution)
+    })
+  }
+}
+
+/// Stored [`Authorized`] group, e.g. contract admins.
+pub struct AuthStore {
+  item: Item<Authorized>,
+}
+
+impl AuthStore {
+  pub const fn new(key: &'static str) -> Self {
+    Self {
+      item: Item::new(key),
+    }
+  }
+
+  /// Load the group, empty if none is stored.
+  pub fn load(&self, storage: &dyn Storage) -> XcosmResult<Authorized> {
+    Ok(self.item.may_load(storage)?.unwrap_or_default())
+  }
+
+  pub fn set(&self, storage: &mut dyn Storage, authorized: &Authorized) -> XcosmResult {
+    Ok(self.item.save(storage, authorized)?)
+  }
+
+  /// Load, modify, then save the group, returning the saved value.
+  pub fn update<F>(&self, storage: &mut dyn Storage, f: F) -> XcosmResult<Authorized>
+  where F: FnOnce(Authorized) -> XcosmResult<Authorized> {
+    let authorized = f(self.load(storage)?)?;
+    self.set(storage, &authorized)?;
+    Ok(authorized)
+  }
+
+  /// Require `sender` to be authorized by the stored group.
+  pub fn assert(&self, storage: &dyn Storage, sender: &Addr) -> XcosmResult {
+    self.load(storage)?.authorize(sender)
+  }
+
+  /// Require `sender` to be authorized by the stored group as of `block`, skipping expired
+  /// members.
+  pub fn assert_at(&self, storage: &dyn Storage, sender: &Addr, block: &BlockInfo) -> XcosmResult {
+    self.load(storage)?.authorize_at(sender, block)
+  }
+
+  /// Replace a bare admin address stored in `admin` with a group of just that address,
+  /// returning the new group. `admin` may use the same key as this store.
+  ///
+  /// Does nothing if no admin is stored, or if `admin` shares this store's key and already
+  /// holds a group, so it is safe to run on every `migrate`.
+  pub fn migrate_from_admin(
+    &self,
+    storage: &mut dyn Storage,
+    admin: &Item<Addr>,
+  ) -> XcosmResult<Option<Authorized>> {
+    if admin.as_slice() == self.item.as_slice() && self.item.may_load(storage).is_ok() {
+      return Ok(None);
+    }
+    let addr = match admin.may_load(storage)? {
+      Some(addr) => addr,
+      None => return Ok(None),
+    };
+    admin.remove(storage);
+    let authorized = Authorized::One(addr);
+    self.set(storage, &authorized)?;
+    Ok(Some(authorized))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use cosmwasm_std::testing::MockStorage;
+
+  use super::*;
+
+  #[test]
+  fn migrate_from_admin_twice() {
+    const AUTH: AuthStore = AuthStore::new("admin");
+    const ADMIN: Item<Addr> = Item::new("admin");
+    let mut storage = MockStorage::new();
+    let admin = Addr::unchecked("admin");
+    ADMIN.save(&mut storage, &admin).unwrap();
+    assert_eq!(
+      AUTH.migrate_from_admin(&mut storage, &ADMIN).unwrap(),
+      Some(Authorized::One(admin.clone()))
+    );
+    assert_eq!(AUTH.migrate_from_admin(&mut storage, &ADMIN).unwrap(), None);
+    assert_eq!(AUTH.load(&storage).unwrap(), Authorized::One(admin));
+  }
+
+  #[test]
+  fn migrate_from_admin_separate_key() {
+    const AUTH: AuthStore = AuthStore::new("auth");
+    const ADMIN: Item<Addr> = Item::new("admin");
+    let mut storage = MockStorage::new();
+    assert_eq!(AUTH.migrate_from_admin(&mut storage, &ADMIN).unwrap(), None);
+    let admin = Addr::unchecked("admin");
+    ADMIN.save(&mut storage, &admin).unwrap();
+    assert_eq!(
+      AUTH.migrate_from_admin(&mut storage, &ADMIN).unwrap(),
+      Some(Authorized::One(admin.clone()))
+    );
+    assert!(!ADMIN.exists(&storage));
+    assert_eq!(AUTH.migrate_from_admin(&mut storage, &ADMIN).unwrap(), None);
+    assert_eq!(AUTH.load(&storage).unwrap(), Authorized::One(admin));
+  }
+}
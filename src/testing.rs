@@ -0,0 +1,131 @@
+use cosmwasm_std::{Addr, Uint128};
+use proptest::{arbitrary::Arbitrary, collection, prelude::*, strategy::BoxedStrategy};
+
+use crate::{Bps, Claim, CoinSet, Distribution, MAX_BPS};
+
+/// Largest amount generated by the [`Arbitrary`] impls, leaving room to sum many sets.
+pub const ARBITRARY_MAX_AMOUNT: u128 = u64::MAX as u128;
+
+/// Generate valid native denoms.
+pub fn denom_strategy() -> impl Strategy<Value=String> {
+  "[a-z][a-z0-9]{2,15}"
+}
+
+/// Generate sets of up to `max_denoms` denoms with amounts in `1..=max_amount`.
+pub fn coin_set_strategy(max_denoms: usize, max_amount: u128) -> impl Strategy<Value=CoinSet> {
+  collection::btree_map(
+    denom_strategy(),
+    (1..=max_amount).prop_map(Uint128::new),
+    0..=max_denoms,
+  )
+  .prop_map(CoinSet::new)
+}
+
+/// Generate basis points within `0..=MAX_BPS`.
+pub fn bps_strategy() -> impl Strategy<Value=Bps> {
+  (0..=MAX_BPS).prop_map(Bps::unbounded)
+}
+
+/// Generate distributions of `1..=max_claims` claims with non-zero bps summing to exactly
+/// [`MAX_BPS`].
+pub fn distribution_strategy(max_claims: usize) -> impl Strategy<Value=Distribution> {
+  (1..=max_claims.max(1))
+    .prop_flat_map(|count| {
+      (
+        collection::btree_set("[a-z]{8}", count),
+        collection::btree_set(1..MAX_BPS, count - 1),
+      )
+    })
+    .prop_map(|(addrs, cuts)| {
+      let bounds: Vec<u32> = std::iter::once(0)
+        .chain(cuts)
+        .chain(std::iter::once(MAX_BPS))
+        .collect();
+      addrs
+        .into_iter()
+        .zip(bounds.windows(2))
+        .map(|(addr, pair)| {
+          (
+            Addr::unchecked(addr),
+            Claim::new(Bps::unbounded(pair[1] - pair[0])),
+          )
+        })
+        .collect::<std::collections::BTreeMap<_, _>>()
+        .into()
+    })
+}
+
+impl Arbitrary for CoinSet {
+  type Parameters = ();
+  type Strategy = BoxedStrategy<Self>;
+
+  fn arbitrary_with(_: ()) -> Self::Strategy {
+    coin_set_strategy(8, ARBITRARY_MAX_AMOUNT).boxed()
+  }
+}
+
+impl Arbitrary for Bps {
+  type Parameters = ();
+  type Strategy = BoxedStrategy<Self>;
+
+  fn arbitrary_with(_: ()) -> Self::Strategy {
+    bps_strategy().boxed()
+  }
+}
+
+impl Arbitrary for Claim {
+  type Parameters = ();
+  type Strategy = BoxedStrategy<Self>;
+
+  fn arbitrary_with(_: ()) -> Self::Strategy {
+    bps_strategy().prop_map(Claim::new).boxed()
+  }
+}
+
+impl Arbitrary for Distribution {
+  type Parameters = ();
+  type Strategy = BoxedStrategy<Self>;
+
+  fn arbitrary_with(_: ()) -> Self::Strategy {
+    distribution_strategy(8).boxed()
+  }
+}
+
+/// Get the outputs of bank messages built by the send helpers, as sent to each address.
+#[cfg(test)]
+pub(crate) fn sent_outputs(msgs: &[cosmwasm_std::CosmosMsg]) -> Vec<(String, CoinSet)> {
+  use cosmwasm_std::{BankMsg, CosmosMsg};
+  msgs
+    .iter()
+    .flat_map(|msg| match msg {
+      CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+        vec![(to_address.clone(), amount.clone().try_into().unwrap())]
+      }
+      #[cfg(all(feature = "cosmwasm_2_0", feature = "protobuf"))]
+      CosmosMsg::Any(msg) => {
+        use prost::Message;
+        crate::proto::MsgMultiSend::decode(msg.value.as_slice())
+          .unwrap()
+          .outputs
+          .into_iter()
+          .map(|io| {
+            let coins = io
+              .coins
+              .into_iter()
+              .map(|coin| cosmwasm_std::Coin::new(coin.amount.parse::<u128>().unwrap(), coin.denom))
+              .collect::<Vec<_>>();
+            (io.address, coins.try_into().unwrap())
+          })
+          .collect()
+      }
+      #[cfg(all(feature = "cosmwasm_2_0", not(feature = "protobuf")))]
+      CosmosMsg::Any(msg) => cosmwasm_std::from_json::<crate::BankMsgMultiSend>(&msg.value)
+        .unwrap()
+        .outputs
+        .into_iter()
+        .map(|io| (io.address.to_string(), io.coins.try_into().unwrap()))
+        .collect(),
+      msg => panic!("not a bank send: {msg:?}"),
+    })
+    .collect()
+}
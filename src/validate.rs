@@ -1,6 +1,9 @@
-use cosmwasm_std::{Addr, Api};
+use std::collections::BTreeMap;
 
-use crate::{XcosmError, XcosmResult};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Api, Coin, Uint128};
+
+use crate::{CoinSet, Denom, DenomFilter, XcosmError, XcosmResult};
 
 pub type ValidateResult<T=()> = Result<T, ValidateError>;
 
@@ -37,3 +40,308 @@ impl<'a, T: AsRef<str>> ApiValidator<'a, Addr> for &'a T {
     })
   }
 }
+
+/// Check a denom against the Cosmos SDK rules, including the `ibc/{hash}` and
+/// `factory/{creator}/{subdenom}` formats.
+fn check_denom(api: &dyn Api, denom: &str) -> ValidateResult {
+  validate_denom(denom)?;
+  if let Some((creator, _)) = denom
+    .strip_prefix("factory/")
+    .and_then(|path| path.split_once('/'))
+  {
+    api
+      .addr_validate(creator)
+      .map_err(|err| ValidateError::NotValid {
+        kind: "denom".to_string(),
+        reason: format!("{}: invalid factory creator: {}", denom, err),
+      })?;
+  }
+  Ok(())
+}
+
+/// Check a denom against the Cosmos SDK rules: 3 to 128 characters, starting with a letter,
+/// using only alphanumerics and `/:._-`, with well-formed `ibc/` and `factory/` prefixes.
+///
+/// Addresses embedded in `factory/` denoms are not validated, see [`ApiValidator`] for that.
+pub fn validate_denom(denom: &str) -> ValidateResult {
+  let not_valid = |reason: &str| ValidateError::NotValid {
+    kind: "denom".to_string(),
+    reason: format!("{}: {}", denom, reason),
+  };
+  if !(3..=128).contains(&denom.len()) {
+    return Err(not_valid("must be between 3 and 128 characters"));
+  }
+  let mut chars = denom.chars();
+  if !chars.next().is_some_and(|c| c.is_ascii_alphabetic()) {
+    return Err(not_valid("must start with a letter"));
+  }
+  if !chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c)) {
+    return Err(not_valid("contains illegal characters"));
+  }
+  if let Some(hash) = denom.strip_prefix("ibc/") {
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+      return Err(not_valid(
+        "ibc denom must be ibc/ followed by a 64 character hex hash",
+      ));
+    }
+  }
+  if let Some(path) = denom.strip_prefix("factory/") {
+    if !path
+      .split_once('/')
+      .is_some_and(|(creator, subdenom)| !creator.is_empty() && !subdenom.is_empty())
+    {
+      return Err(not_valid(
+        "factory denom must be factory/{creator}/{subdenom}",
+      ));
+    }
+  }
+  Ok(())
+}
+
+impl<'a, T: AsRef<str>> ApiValidator<'a, Denom> for &'a T {
+  fn api_validate(self, api: &'a dyn Api) -> XcosmResult<Denom> {
+    check_denom(api, self.as_ref())?;
+    Ok(Denom::unchecked(self.as_ref()))
+  }
+}
+
+/// Build a [`CoinSet`] from raw coins, validating each denom as a [`Denom`].
+impl<'a> ApiValidator<'a, CoinSet> for &'a Vec<Coin> {
+  fn api_validate(self, api: &'a dyn Api) -> XcosmResult<CoinSet> {
+    let mut coins = CoinSet::default();
+    for coin in self {
+      let denom: Denom = api.validate(&coin.denom)?;
+      coins.try_insert(&denom, coin.amount)?;
+    }
+    Ok(coins)
+  }
+}
+
+/// Validator requiring all denoms in a [`CoinSet`] to pass a [`DenomFilter`], or dropping
+/// the rest if the filter is configured to.
+impl Validator<CoinSet, CoinSet> for &DenomFilter {
+  fn validate(self, coins: CoinSet) -> XcosmResult<CoinSet> {
+    self.apply(coins)
+  }
+}
+
+/// Inclusive amount range for a single denom.
+#[cw_serde]
+pub struct CoinBounds {
+  pub denom: String,
+  pub min: Uint128,
+  pub max: Uint128,
+}
+
+impl CoinBounds {
+  /// Create new [`CoinBounds`].
+  ///
+  /// Panics if `min` is greater than `max`.
+  pub fn new(denom: impl Into<String>, min: Uint128, max: Uint128) -> Self {
+    assert!(min <= max, "coin bounds min must not exceed max");
+    Self {
+      denom: denom.into(),
+      min,
+      max,
+    }
+  }
+
+  /// Require the amount to be within bounds.
+  fn check_amount(&self, amount: Uint128) -> ValidateResult {
+    if self.min > self.max {
+      return Err(ValidateError::NotValid {
+        kind: "bounds".to_string(),
+        reason: format!("{} min {} exceeds max {}", self.denom, self.min, self.max),
+      });
+    }
+    if amount < self.min || amount > self.max {
+      return Err(ValidateError::NotValid {
+        kind: "amount".to_string(),
+        reason: format!(
+          "{}{} is outside {}..={}",
+          amount, self.denom, self.min, self.max
+        ),
+      });
+    }
+    Ok(())
+  }
+}
+
+impl<'a> Validator<&'a Coin, ()> for &CoinBounds {
+  /// Require the coin to match the denom and be within bounds.
+  fn validate(self, coin: &'a Coin) -> XcosmResult {
+    if coin.denom != self.denom {
+      return Err(
+        ValidateError::NotValid {
+          kind: "denom".to_string(),
+          reason: format!("expected {}, got {}", self.denom, coin.denom),
+        }
+        .into(),
+      );
+    }
+    self.check_amount(coin.amount).map_err(Into::into)
+  }
+}
+
+/// Per-denom amount bounds for a [`CoinSet`].
+#[cw_serde]
+pub struct CoinSetBounds {
+  pub bounds: BTreeMap<String, CoinBounds>,
+  /// Allow denoms without configured bounds rather than rejecting them.
+  pub allow_unbounded: bool,
+}
+
+impl CoinSetBounds {
+  pub fn new(bounds: impl IntoIterator<Item=CoinBounds>, allow_unbounded: bool) -> Self {
+    Self {
+      bounds: bounds
+        .into_iter()
+        .map(|bound| (bound.denom.clone(), bound))
+        .collect(),
+      allow_unbounded,
+    }
+  }
+}
+
+impl<'a> Validator<&'a CoinSet, ()> for &CoinSetBounds {
+  /// Require every bounded denom to be within bounds, treating missing denoms as zero.
+  fn validate(self, coins: &'a CoinSet) -> XcosmResult {
+    if !self.allow_unbounded {
+      if let Some(denom) = coins.keys().find(|denom| !self.bounds.contains_key(*denom)) {
+        return Err(
+          ValidateError::NotValid {
+            kind: "denom".to_string(),
+            reason: format!("{} has no configured bounds", denom),
+          }
+          .into(),
+        );
+      }
+    }
+    for bound in self.bounds.values() {
+      bound.check_amount(coins.get(&bound.denom).copied().unwrap_or_default())?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn coins(coins: &str) -> CoinSet {
+    coins.parse().unwrap()
+  }
+
+  #[test]
+  fn denom_filter_passes_clean_set() {
+    let filter = DenomFilter::allow(["uatom", "uosmo"]);
+    assert_eq!(
+      filter.validate(coins("10uatom,5uosmo")).unwrap(),
+      coins("10uatom,5uosmo")
+    );
+    assert_eq!(
+      filter.validate(CoinSet::default()).unwrap(),
+      CoinSet::default()
+    );
+  }
+
+  #[test]
+  fn denom_filter_rejects_disallowed_denom() {
+    let filter = DenomFilter::allow(["uatom"]);
+    assert!(filter.validate(coins("10uatom,5uosmo")).is_err());
+    assert_eq!(
+      (&filter.dropping())
+        .validate(coins("10uatom,5uosmo"))
+        .unwrap(),
+      coins("10uatom")
+    );
+  }
+
+  #[test]
+  fn validate_native_denoms() {
+    for denom in [
+      "uatom",
+      "stake",
+      "factory/osmo1creator/ufoo",
+      "gamm/pool/1",
+      "a.b:c_d-e",
+    ] {
+      assert!(validate_denom(denom).is_ok(), "{denom}");
+    }
+  }
+
+  #[test]
+  fn validate_ibc_denoms() {
+    let hash = "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+    assert!(validate_denom(&format!("ibc/{hash}")).is_ok());
+    assert!(validate_denom(&format!("ibc/{}", hash.to_lowercase())).is_ok());
+    assert!(validate_denom(&format!("ibc/{}", &hash[1..])).is_err());
+    assert!(validate_denom(&format!("ibc/{}G", &hash[1..])).is_err());
+  }
+
+  #[test]
+  fn validate_rejects_bad_denoms() {
+    let long = format!("u{}", "a".repeat(128));
+    for denom in [
+      "",
+      "ua",
+      long.as_str(),
+      "1uatom",
+      "/uatom",
+      "u atom",
+      "uatom!",
+      "factory/",
+      "factory/creator",
+      "factory//ufoo",
+    ] {
+      assert!(validate_denom(denom).is_err(), "{denom:?}");
+    }
+  }
+
+  #[test]
+  fn api_validates_factory_creator() {
+    let api = cosmwasm_std::testing::MockApi::default();
+    let creator = api.addr_make("creator");
+    let denom: Denom = (&api as &dyn Api)
+      .validate(&format!("factory/{creator}/ufoo"))
+      .unwrap();
+    assert_eq!(denom.as_str(), format!("factory/{creator}/ufoo"));
+    let res: XcosmResult<Denom> = (&api as &dyn Api).validate(&"factory/notanaddress/ufoo");
+    assert!(res.is_err());
+  }
+
+  #[test]
+  fn coin_bounds() {
+    let bounds = CoinBounds::new("uatom", Uint128::new(10), Uint128::new(100));
+    assert!(bounds.validate(&Coin::new(9u128, "uatom")).is_err());
+    assert!(bounds.validate(&Coin::new(10u128, "uatom")).is_ok());
+    assert!(bounds.validate(&Coin::new(100u128, "uatom")).is_ok());
+    assert!(bounds.validate(&Coin::new(101u128, "uatom")).is_err());
+    assert!(bounds.validate(&Coin::new(50u128, "uosmo")).is_err());
+  }
+
+  #[test]
+  fn coin_set_bounds() {
+    let bounds = CoinSetBounds::new(
+      [
+        CoinBounds::new("uatom", Uint128::new(10), Uint128::new(100)),
+        CoinBounds::new("uosmo", Uint128::zero(), Uint128::new(5)),
+      ],
+      false,
+    );
+    assert!(bounds.validate(&coins("50uatom,5uosmo")).is_ok());
+    assert!(bounds.validate(&coins("50uatom")).is_ok());
+    // below min, including a missing denom counted as zero
+    assert!(bounds.validate(&coins("9uatom")).is_err());
+    assert!(bounds.validate(&coins("5uosmo")).is_err());
+    // above max
+    assert!(bounds.validate(&coins("50uatom,6uosmo")).is_err());
+    // unbounded denoms
+    assert!(bounds.validate(&coins("50uatom,1ujuno")).is_err());
+    let lenient = CoinSetBounds {
+      allow_unbounded: true,
+      ..bounds
+    };
+    assert!(lenient.validate(&coins("50uatom,1ujuno")).is_ok());
+  }
+}